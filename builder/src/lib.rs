@@ -1,6 +1,7 @@
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{parse_macro_input, DeriveInput};
+use quote::{format_ident, quote, ToTokens};
+use std::collections::HashMap;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Generics};
 
 mod utils;
 use utils::*;
@@ -11,32 +12,699 @@ pub fn derive(input: TokenStream) -> TokenStream {
     // Pretty print DeriveInput if syn extra-traits enabled.
     // println!("{:#?}", ast);
 
-    let derive_struct_ident = ast.ident;
-    let builder_struct_ident = format_ident!("{}Builder", derive_struct_ident);
-    let struct_data = ast.data;
+    let derive_struct_ident = &ast.ident;
+    // Attribute errors of the struct and of every field, reported together.
+    let mut errors = Vec::new();
 
-    let helpers = match init_field_macro_helpers(&struct_data) {
-        Ok(vec) => vec,
+    let struct_attributes = match extract_attributes(
+        &ast.attrs,
+        "builder",
+        &[
+            "typestate",
+            "pattern=",
+            "default",
+            "setter.into",
+            "setter.strip_option",
+            "try_setter",
+            "vis=",
+            "validate=",
+            "name=",
+            "build_fn.name=",
+            "build_fn.error=",
+            "build_fn.skip",
+            "derive(..)",
+            "struct_attr(..)",
+            "no_std",
+            "serde",
+            "from_args",
+        ],
+        "expected one of `typestate`, `pattern`, `default`, `setter(into, strip_option)`, \
+         `try_setter`, `vis`, `validate`, `name`, `build_fn(name, error, skip)`, `derive(...)`, \
+         `struct_attr(...)`, `no_std`, `serde`, `from_args`",
+    ) {
+        Ok(attrs) => attrs,
         Err(e) => {
-            return e;
+            errors.push(e);
+            HashMap::new()
         }
     };
+    let typestate = struct_attributes.contains_key("typestate");
+    // Typestate setters consume the builder, since setting a required field changes its type.
+    let pattern = match struct_attributes.get("pattern") {
+        None => {
+            if typestate {
+                BuilderPattern::Owned
+            } else {
+                BuilderPattern::Mutable
+            }
+        }
+        Some(name) => match name.as_deref().and_then(BuilderPattern::from_name) {
+            Some(BuilderPattern::Owned) => BuilderPattern::Owned,
+            Some(_) if typestate => {
+                errors.push(attribute_error(
+                    name,
+                    derive_struct_ident,
+                    "`builder(typestate)` requires `builder(pattern = \"owned\")`",
+                ));
+                BuilderPattern::Owned
+            }
+            Some(pattern) => pattern,
+            None => {
+                errors.push(attribute_error(
+                    name,
+                    derive_struct_ident,
+                    "expected `builder(pattern = \"owned\" | \"mutable\" | \"immutable\")`",
+                ));
+                BuilderPattern::Mutable
+            }
+        },
+    };
+    // Seed unset fields from the struct's own Default impl.
+    let struct_default = struct_attributes.contains_key("default");
+
+    // #[builder(validate = "path::to::fn")], called with the candidate value before build() returns it.
+    let validate_fn = match struct_attributes.get("validate") {
+        Some(path) => match path.as_deref().map(syn::parse_str::<syn::Path>) {
+            Some(Ok(path)) => Some(path),
+            _ => {
+                errors.push(attribute_error(
+                    path,
+                    derive_struct_ident,
+                    "expected `builder(validate = \"path::to::fn\")`",
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+
+    // The builder, its error type, builder() and build() share the struct's visibility by default.
+    let builder_vis = match struct_attributes.get("vis") {
+        Some(vis) => match parse_visibility(vis, derive_struct_ident) {
+            Ok(vis) => vis,
+            Err(e) => {
+                errors.push(e);
+                ast.vis.clone()
+            }
+        },
+        None => ast.vis.clone(),
+    };
+
+    // #[builder(name = "ConnOptions")] replaces the `{Struct}Builder` name.
+    let builder_name = match struct_attributes.get("name") {
+        Some(name) => match name.as_deref().map(syn::parse_str::<syn::Ident>) {
+            Some(Ok(name)) => Some(name),
+            _ => {
+                errors.push(attribute_error(
+                    name,
+                    derive_struct_ident,
+                    "expected `builder(name = \"BuilderName\")`",
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+    // #[builder(build_fn(name = "finish"))]
+    let build_fn_name = match struct_attributes.get("build_fn.name") {
+        Some(name) => match name.as_deref().map(syn::parse_str::<syn::Ident>) {
+            Some(Ok(name)) => name,
+            _ => {
+                errors.push(attribute_error(
+                    name,
+                    derive_struct_ident,
+                    "expected `builder(build_fn(name = \"method_name\"))`",
+                ));
+                format_ident!("build")
+            }
+        },
+        None => format_ident!("build"),
+    };
+    // #[builder(build_fn(error = "MyError"))], converted from the builder's error with From.
+    let build_fn_error = match struct_attributes.get("build_fn.error") {
+        Some(error) => match error.as_deref().map(syn::parse_str::<syn::Type>) {
+            Some(Ok(error)) => Some(error),
+            _ => {
+                errors.push(attribute_error(
+                    error,
+                    derive_struct_ident,
+                    "expected `builder(build_fn(error = \"path::to::Error\"))`",
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+    // #[builder(build_fn(skip))] leaves the build method to be written by hand.
+    let build_fn_skip = flag_attribute(&struct_attributes, "build_fn.skip").unwrap_or(false);
+
+    // #[builder(serde)] derives Deserialize on the builder to load it from config files, not
+    // possible for typestate builders whose type tracks the set fields, nor borrowed data.
+    let builder_deserialize = flag_attribute(&struct_attributes, "serde").unwrap_or(false);
+    if builder_deserialize {
+        let error = if !cfg!(feature = "serde") {
+            Some("`builder(serde)` requires the `serde` feature of derive_builder")
+        } else if typestate {
+            Some("`builder(serde)` is not supported with `builder(typestate)`")
+        } else if ast.generics.lifetimes().next().is_some() {
+            Some("`builder(serde)` is not supported on structs with lifetime parameters")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            errors.push(syn::Error::new_spanned(derive_struct_ident, error));
+        }
+    }
+
+    // Generated code uses core paths, and for the errors of sub-builders and validate alloc's
+    // Box and String, taken from the alloc crate with #[builder(no_std)].
+    let no_std = flag_attribute(&struct_attributes, "no_std").unwrap_or(false);
+    let alloc_crate = if no_std {
+        quote! { ::alloc }
+    } else {
+        quote! { ::std }
+    };
+
+    // #[builder(from_args)] parses command-line flags into the builder, which needs std and
+    // sets fields in place.
+    let from_args = flag_attribute(&struct_attributes, "from_args").unwrap_or(false);
+    if from_args {
+        let error = if typestate {
+            Some("`builder(from_args)` is not supported with `builder(typestate)`")
+        } else if no_std {
+            Some("`builder(from_args)` is not supported with `builder(no_std)`")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            errors.push(syn::Error::new_spanned(derive_struct_ident, error));
+        }
+    }
+
+    // #[builder(derive(Clone, Debug))] and #[builder(struct_attr(...))] on the builder struct.
+    let builder_derives = match parse_token_list::<syn::Path>(&struct_attributes, "derive") {
+        Ok(derives) => derives,
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
+    };
+    let builder_struct_attrs =
+        match parse_token_list::<syn::Meta>(&struct_attributes, "struct_attr") {
+            Ok(attrs) => attrs,
+            Err(e) => {
+                errors.push(e);
+                Vec::new()
+            }
+        };
+
+    let options = BuilderOptions {
+        struct_attributes,
+        typestate,
+        pattern,
+        struct_default,
+        validate_fn,
+        builder_vis,
+        build_fn_name,
+        build_fn_error,
+        build_fn_skip,
+        builder_derives,
+        builder_struct_attrs,
+        builder_deserialize,
+        from_args,
+        no_std,
+        alloc_crate,
+    };
+
+    // A builder for the struct itself, or one per struct-like variant of an enum.
+    let targets = match &ast.data {
+        // Tuple structs are built as `Rgb { 0: ..., 1: ... }`, unit structs as `Marker {}`.
+        Data::Struct(data_struct) => vec![BuilderTarget {
+            fields: &data_struct.fields,
+            builder_ident: builder_name
+                .unwrap_or_else(|| format_ident!("{}Builder", derive_struct_ident)),
+            constructor_ident: format_ident!("builder"),
+            value_path: quote! { #derive_struct_ident },
+            is_variant: false,
+            paths_ident: format_ident!("__FIELD_PATHS"),
+            doc: doc_comment(&ast.attrs),
+        }],
+        Data::Enum(data_enum) => {
+            if options.struct_default {
+                errors.push(syn::Error::new_spanned(
+                    derive_struct_ident,
+                    "`builder(default)` is not supported on enums",
+                ));
+            }
+            // Each variant gets its own builder, which one name can't cover.
+            if builder_name.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    derive_struct_ident,
+                    "`builder(name = \"...\")` is not supported on enums",
+                ));
+            }
+            // Variants share the enum's options, only their fields take #[builder(...)].
+            for variant in &data_enum.variants {
+                if let Err(e) = extract_attributes(
+                    &variant.attrs,
+                    "builder",
+                    &[],
+                    "`builder(...)` is not supported on enum variants, put the options on the \
+                     enum or on the variant's fields",
+                ) {
+                    errors.push(e);
+                }
+            }
+            // Tuple variants are built as `Shape::Point { 0: ..., 1: ... }`, unit variants as
+            // `Shape::Empty {}`.
+            let targets = data_enum
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_ident = &variant.ident;
+                    BuilderTarget {
+                        fields: &variant.fields,
+                        builder_ident: format_ident!(
+                            "{}{}Builder",
+                            derive_struct_ident,
+                            variant_ident
+                        ),
+                        constructor_ident: format_ident!(
+                            "{}_builder",
+                            snake_case(&variant_ident.to_string())
+                        ),
+                        value_path: quote! { #derive_struct_ident::#variant_ident },
+                        is_variant: true,
+                        paths_ident: format_ident!("__{}_FIELD_PATHS", variant_ident),
+                        doc: doc_comment(&variant.attrs),
+                    }
+                })
+                .collect::<Vec<_>>();
+            if targets.is_empty() {
+                return syn::Error::new_spanned(
+                    derive_struct_ident,
+                    "`derive(Builder)` on an enum requires at least one variant",
+                )
+                .to_compile_error()
+                .into();
+            }
+            targets
+        }
+        Data::Union(data_union) => {
+            return syn::Error::new_spanned(
+                data_union.union_token,
+                "`derive(Builder)` does not support unions",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let mut expanded = proc_macro2::TokenStream::new();
+    for target in targets {
+        match expand_builder(&ast, &options, target) {
+            Ok(tokens) => expanded.extend(tokens),
+            Err(e) => errors.push(e),
+        }
+    }
+    match combine_errors(errors) {
+        Some(error) => error.to_compile_error().into(),
+        None => expanded.into(),
+    }
+}
+
+// Options from #[builder(...)] on the struct or enum, shared by all of its builders.
+struct BuilderOptions {
+    struct_attributes: HashMap<String, Option<AttrValue>>,
+    typestate: bool,
+    pattern: BuilderPattern,
+    struct_default: bool,
+    validate_fn: Option<syn::Path>,
+    builder_vis: syn::Visibility,
+    build_fn_name: syn::Ident,
+    build_fn_error: Option<syn::Type>,
+    build_fn_skip: bool,
+    builder_derives: Vec<syn::Path>,
+    builder_struct_attrs: Vec<syn::Meta>,
+    builder_deserialize: bool,
+    from_args: bool,
+    no_std: bool,
+    // `::alloc` for #[builder(no_std)], `::std` otherwise.
+    alloc_crate: proc_macro2::TokenStream,
+}
+
+// What a single builder constructs: the struct, or one variant of an enum.
+struct BuilderTarget<'a> {
+    fields: &'a Fields,
+    builder_ident: syn::Ident,
+    // builder() for structs, circle_builder() for `Shape::Circle`.
+    constructor_ident: syn::Ident,
+    // Path used to construct the value, `Command` or `Shape::Circle`.
+    value_path: proc_macro2::TokenStream,
+    is_variant: bool,
+    // Hidden `&'static str` const of the enum or struct listing the paths missing_fields() may
+    // report, separated by '\0': `__FIELD_PATHS`, `__Circle_FIELD_PATHS` for `Shape::Circle`.
+    paths_ident: syn::Ident,
+    // Doc comment of the struct or variant, heading the from_args() help.
+    doc: String,
+}
+
+fn expand_builder(
+    ast: &DeriveInput,
+    options: &BuilderOptions,
+    target: BuilderTarget,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let derive_struct_ident = &ast.ident;
+    let builder_struct_ident = &target.builder_ident;
+    let builder_error_ident = format_ident!("{}Error", builder_struct_ident);
+    let constructor_ident = &target.constructor_ident;
+    let value_path = &target.value_path;
+    let BuilderOptions {
+        struct_attributes,
+        typestate,
+        pattern,
+        struct_default,
+        validate_fn,
+        builder_vis,
+        build_fn_name,
+        build_fn_error,
+        build_fn_skip,
+        builder_derives,
+        builder_struct_attrs,
+        builder_deserialize,
+        from_args,
+        no_std,
+        alloc_crate,
+    } = options;
+    let (typestate, pattern, struct_default) = (*typestate, *pattern, *struct_default);
+
+    // The builder carries the same generic parameters and where clause as the derived struct.
+    let struct_generics = &ast.generics;
+    let (impl_generics, ty_generics, where_clause) = struct_generics.split_for_impl();
+
+    let helpers = init_field_macro_helpers(
+        target.fields,
+        struct_attributes,
+        builder_vis,
+        struct_generics,
+    )?;
+    // fill_from_env() sets fields in place, which typestate builders can't track, and needs std.
+    if let Some(h) = helpers.iter().find(|h| h.has_env_var()) {
+        if typestate {
+            return Err(syn::Error::new_spanned(
+                h.field_type(),
+                "`builder(env = \"...\")` is not supported with `builder(typestate)`",
+            ));
+        }
+        if *no_std {
+            return Err(syn::Error::new_spanned(
+                h.field_type(),
+                "`builder(env = \"...\")` is not supported with `builder(no_std)`",
+            ));
+        }
+    }
+
+    // Builder methods need distinct names: a field named `clear` can't have a `clear` setter next to
+    // clear(), nor can fields `port` and `get_port` share `get_port`.
+    let mut method_names = Vec::new();
+    if !*build_fn_skip {
+        method_names.push(
+            build_fn_name
+                .to_string()
+                .trim_start_matches("r#")
+                .to_owned(),
+        );
+    }
+    method_names.push("missing_fields".to_owned());
+    if !typestate {
+        method_names.extend(["merge", "or", "clear"].map(str::to_owned));
+    }
+    if helpers.iter().any(|h| h.has_env_var()) {
+        method_names.extend(["fill_from_env", "from_env"].map(str::to_owned));
+    }
+    if *from_args {
+        method_names.push("from_args".to_owned());
+    }
+    let mut method_owners = method_names
+        .into_iter()
+        .map(|name| {
+            let owner = format!("the builder's `{}` method", name);
+            (name, owner)
+        })
+        .collect::<HashMap<_, _>>();
+    let mut errors = Vec::new();
+    for h in &helpers {
+        for (name, kind, option) in h.method_names(typestate) {
+            let owner = format!("the {} `{}` of field `{}`", kind, name, h.display_name());
+            match method_owners.get(&name) {
+                Some(previous) => errors.push(syn::Error::new(
+                    h.span(),
+                    format!(
+                        "{} conflicts with {}, rename it with `builder({} = \"...\")`",
+                        owner, previous, option
+                    ),
+                )),
+                None => {
+                    method_owners.insert(name, owner);
+                }
+            }
+        }
+    }
+    if let Some(error) = combine_errors(errors) {
+        return Err(error);
+    }
+
+    // Typestate builders track every required field in a `const __FIELD_IS_SET: bool` parameter
+    // appended to the struct's own generics, so build() only exists once all of them are `true`.
+    let required_helpers = if typestate {
+        helpers
+            .iter()
+            .filter(|h| h.is_required())
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let mut builder_generics = struct_generics.clone();
+    // Defaults must be trailing, which the state parameters can't follow.
+    if !required_helpers.is_empty() {
+        for param in &mut builder_generics.params {
+            match param {
+                GenericParam::Type(ty) => {
+                    ty.eq_token = None;
+                    ty.default = None;
+                }
+                GenericParam::Const(constant) => {
+                    constant.eq_token = None;
+                    constant.default = None;
+                }
+                GenericParam::Lifetime(_) => {}
+            }
+        }
+    }
+    for h in &required_helpers {
+        let param = h.typestate_param_ident();
+        builder_generics
+            .params
+            .push(parse_quote!(const #param: bool));
+    }
+    let (builder_impl_generics, builder_ty_generics, _) = builder_generics.split_for_impl();
+    let struct_generic_arguments = generic_arguments(struct_generics);
+    // CommandBuilder<'a, T, STATE_1, ...>, the states being `true`, `false` or the parameter itself.
+    let builder_type_with_states = |states: &[proc_macro2::TokenStream]| {
+        if struct_generic_arguments.is_empty() && states.is_empty() {
+            quote! { #builder_struct_ident }
+        } else {
+            quote! { #builder_struct_ident <#(#struct_generic_arguments,)* #(#states),*> }
+        }
+    };
+    let all_states_as = |state: proc_macro2::TokenStream| {
+        required_helpers
+            .iter()
+            .map(|_| state.clone())
+            .collect::<Vec<_>>()
+    };
+    let unset_builder_type = builder_type_with_states(&all_states_as(quote! { false }));
+    let set_builder_type = builder_type_with_states(&all_states_as(quote! { true }));
+
+    // A parameter only used by skipped fields, or by other variants of an enum, is held by a
+    // marker instead.
+    let uses_marker = struct_generics.params.iter().any(|param| {
+        let ident = match param {
+            GenericParam::Lifetime(lifetime) => &lifetime.lifetime.ident,
+            GenericParam::Type(ty) => &ty.ident,
+            GenericParam::Const(_) => return false,
+        };
+        !helpers.iter().any(|h| h.uses_param(ident))
+    });
+    let marker_attr = if *builder_deserialize {
+        quote! { #[serde(skip)] }
+    } else {
+        quote! {}
+    };
+    let (marker_definition, marker_init) = if uses_marker {
+        (
+            quote! {
+                #marker_attr
+                __marker: ::core::marker::PhantomData<fn() -> #derive_struct_ident #ty_generics>,
+            },
+            quote! { __marker: ::core::marker::PhantomData, },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
 
     let builder_field_definition_block = helpers.iter().map(|h| h.field_defintion_inner_form());
+    // Immutable setters return an updated clone of the builder, unless Clone is derived already.
+    let derives_clone = builder_derives.iter().any(|path| {
+        path.segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Clone")
+    });
+    let mut derives = builder_derives
+        .iter()
+        .map(ToTokens::to_token_stream)
+        .collect::<Vec<_>>();
+    if pattern == BuilderPattern::Immutable && !derives_clone {
+        derives.push(quote! { ::core::clone::Clone });
+    }
+    if *builder_deserialize {
+        derives.push(quote! { ::serde::Deserialize });
+    }
+    let builder_derive_attr = if derives.is_empty() {
+        quote! {}
+    } else {
+        quote! { #[derive(#(#derives),*)] }
+    };
     let builder_definition_block = quote! {
-        pub struct #builder_struct_ident {
+        #builder_derive_attr
+        #(#[#builder_struct_attrs])*
+        #builder_vis struct #builder_struct_ident #builder_generics #where_clause {
             // field_name: Option<field_type>, ...
             #(#builder_field_definition_block)*
+            #marker_definition
         }
     };
 
+    // An enum value may be any of its variants, so only structs convert back into a builder.
+    let to_builder_method = if target.is_variant {
+        quote! {}
+    } else {
+        quote! {
+            // The higher-ranked bound keeps a non-Clone struct from failing to compile here,
+            // to_builder() is then simply not callable on it.
+            #builder_vis fn to_builder(&self) -> #set_builder_type
+            where
+                for<'__builder> Self: ::core::clone::Clone,
+            {
+                ::core::convert::From::from(::core::clone::Clone::clone(self))
+            }
+        }
+    };
+    // The missing paths are `&'static str` slices of a const listing every possible one in field
+    // order, nested paths being joined at compile time from the consts of the nested types.
+    let paths_ident = &target.paths_ident;
+    let paths_parts = helpers
+        .iter()
+        .filter_map(|h| h.field_paths_part())
+        .collect::<Vec<_>>();
+    let paths_value = if paths_parts.iter().all(|(prefix, _)| prefix.is_empty()) {
+        let paths = helpers
+            .iter()
+            .filter(|h| h.field_paths_part().is_some())
+            .map(|h| h.display_name())
+            .collect::<Vec<_>>()
+            .join("\0");
+        quote! { #paths }
+    } else {
+        let parts = paths_parts
+            .iter()
+            .map(|(prefix, paths)| quote! { (#prefix, #paths) });
+        quote! {{
+            const PARTS: &[(&str, &str)] = &[#(#parts),*];
+            // Writes each path of the parts with its prefix into `out`, as far as it goes, and
+            // returns the length of the whole.
+            const fn join(parts: &[(&str, &str)], out: &mut [u8]) -> usize {
+                let mut len = 0;
+                let mut part = 0;
+                while part < parts.len() {
+                    let (prefix, paths) = (parts[part].0.as_bytes(), parts[part].1.as_bytes());
+                    let (mut start, mut end) = (0, 0);
+                    while end <= paths.len() {
+                        if (end == paths.len() || paths[end] == 0) && end > start {
+                            if len > 0 {
+                                if len < out.len() {
+                                    out[len] = 0;
+                                }
+                                len += 1;
+                            }
+                            let mut i = 0;
+                            while i < prefix.len() + end - start {
+                                if len < out.len() {
+                                    out[len] = if i < prefix.len() {
+                                        prefix[i]
+                                    } else {
+                                        paths[start + i - prefix.len()]
+                                    };
+                                }
+                                len += 1;
+                                i += 1;
+                            }
+                        }
+                        if end == paths.len() || paths[end] == 0 {
+                            start = end + 1;
+                        }
+                        end += 1;
+                    }
+                    part += 1;
+                }
+                len
+            }
+            const LEN: usize = join(PARTS, &mut []);
+            const BYTES: [u8; LEN] = {
+                let mut bytes = [0; LEN];
+                join(PARTS, &mut bytes);
+                bytes
+            };
+            match ::core::str::from_utf8(&BYTES) {
+                ::core::result::Result::Ok(paths) => paths,
+                ::core::result::Result::Err(_) => ::core::panic!("field paths are UTF-8"),
+            }
+        }}
+    };
+    let field_paths_item = quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_upper_case_globals)]
+        #builder_vis const #paths_ident: &'static str = #paths_value;
+    };
     let builder_constructor_inner = helpers.iter().map(|h| h.field_construction_inner_form());
     let builder_constructor_block = quote! {
-        impl #derive_struct_ident {
-            pub fn builder() -> #builder_struct_ident {
+        impl #impl_generics #derive_struct_ident #ty_generics #where_clause {
+            #builder_vis fn #constructor_ident() -> #unset_builder_type {
                 #builder_struct_ident {
                     // field_name: None, ...
                     #(#builder_constructor_inner)*
+                    #marker_init
+                }
+            }
+
+            #to_builder_method
+
+            #field_paths_item
+        }
+    };
+
+    // Turn an existing value back into a builder with every field set.
+    let builder_from_value_inner = helpers.iter().map(|h| h.field_from_value_form());
+    let builder_from_value_block = if target.is_variant {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics ::core::convert::From<#derive_struct_ident #ty_generics> for #set_builder_type #where_clause {
+                fn from(value: #derive_struct_ident #ty_generics) -> Self {
+                    #builder_struct_ident {
+                        #(#builder_from_value_inner)*
+                        #marker_init
+                    }
                 }
             }
         }
@@ -44,29 +712,582 @@ pub fn derive(input: TokenStream) -> TokenStream {
 
     let builder_field_setter = helpers
         .iter()
-        .map(|h: &BuilderMacroFieldHelper<'_>| h.field_setter_from());
-    let build_inner = helpers.iter().map(|h| h.field_build_inner_form());
-    let builder_build_method = quote! {
-        fn build(&self) -> std::result::Result<#derive_struct_ident, std::boxed::Box<dyn std::error::Error>> {
-            Ok(#derive_struct_ident {
+        .filter(|h| !(typestate && h.is_required()))
+        .map(|h: &BuilderMacroFieldHelper<'_>| h.field_setter_from(pattern));
+    let build_inner = helpers
+        .iter()
+        .map(|h| h.field_build_inner_form(&builder_error_ident, pattern));
+    // build() clones every field, so generic field types must be Clone, and Default when defaulted.
+    let type_params = struct_generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+    let mut build_bounds = if pattern == BuilderPattern::Owned {
+        Vec::new()
+    } else {
+        helpers
+            .iter()
+            .filter_map(|h| h.field_clone_bound(&type_params))
+            .collect::<Vec<_>>()
+    };
+    build_bounds.extend(
+        helpers
+            .iter()
+            .filter_map(|h| h.field_default_bound(&type_params)),
+    );
+    if struct_default && !type_params.is_empty() {
+        build_bounds.push(quote! { #derive_struct_ident #ty_generics: ::core::default::Default });
+    }
+    let build_where_clause = if build_bounds.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#build_bounds),* }
+    };
+    let build_receiver = pattern.build_receiver();
+    let build_default_value = if struct_default {
+        quote! {
+            let __default: #derive_struct_ident #ty_generics = ::core::default::Default::default();
+        }
+    } else {
+        quote! {}
+    };
+    let build_validation = match validate_fn {
+        Some(validate_fn) => quote! {
+            #validate_fn(&__value).map_err(|error| {
+                #builder_error_ident::ValidationError(#alloc_crate::string::ToString::to_string(&error))
+            })?;
+        },
+        None => quote! {},
+    };
+    // Computed fields are evaluated before the value is constructed, in declaration order, each
+    // seeing the other fields and the computed ones before it.
+    let build_value = if helpers.iter().any(|h| h.is_computed()) {
+        let build_locals = helpers
+            .iter()
+            .map(|h| h.field_build_local_form(&builder_error_ident, pattern));
+        let build_compute = helpers.iter().enumerate().map(|(index, h)| {
+            let in_scope = helpers
+                .iter()
+                .enumerate()
+                .filter(|(other, other_h)| {
+                    *other != index && (!other_h.is_computed() || *other < index)
+                })
+                .map(|(_, other_h)| other_h)
+                .collect::<Vec<_>>();
+            h.field_compute_form(&in_scope)
+        });
+        let build_members = helpers.iter().map(|h| h.field_build_member_form());
+        quote! {
+            #(#build_locals)*
+            #(#build_compute)*
+            let __value = #value_path {
+                #(#build_members)*
+            };
+        }
+    } else {
+        quote! {
+            let __value = #value_path {
                 #(#build_inner)*
-            })
+            };
+        }
+    };
+    let build_body = quote! {
+        #build_default_value
+        #build_value
+        #build_validation
+        ::core::result::Result::Ok(__value)
+    };
+    let builder_build_method = match build_fn_error {
+        _ if *build_fn_skip => quote! {},
+        // The generated body still fails with the builder's error, converted on return.
+        Some(build_fn_error) => quote! {
+            #builder_vis fn #build_fn_name(#build_receiver) -> ::core::result::Result<#derive_struct_ident #ty_generics, #build_fn_error> #build_where_clause {
+                let __result: ::core::result::Result<#derive_struct_ident #ty_generics, #builder_error_ident> =
+                    (|| { #build_body })();
+                __result.map_err(::core::convert::From::from)
+            }
+        },
+        None => quote! {
+            #builder_vis fn #build_fn_name(#build_receiver) -> ::core::result::Result<#derive_struct_ident #ty_generics, #builder_error_ident> #build_where_clause {
+                #build_body
+            }
+        },
+    };
+
+    // Items for the parents nesting this builder with #[builder(sub_builder)], hidden since only
+    // generated code uses them: __sub_builder_build(), __sub_builder_from() and
+    // __sub_builder_merge() of the builder stand for its build method, From impl and merge(),
+    // whatever their names.
+    // Server::__sub_builder_error() tells the builders that can't nest it why, checked when they
+    // compile: typestate builders change type as fields are set, build_fn(skip) ones have no
+    // build method, and owned ones can only be built by an owned parent.
+    let sub_builder_hooks_block = if target.is_variant {
+        quote! {}
+    } else {
+        let error = if typestate {
+            Some("`builder(sub_builder)` does not support nesting a `builder(typestate)` builder")
+        } else if *build_fn_skip {
+            Some("`builder(sub_builder)` does not support nesting a `builder(build_fn(skip))` builder")
+        } else {
+            None
+        };
+        let by_ref_error = error.or(if pattern == BuilderPattern::Owned {
+            Some("`builder(sub_builder)` of a `builder(pattern = \"owned\")` builder requires the parent builder to be owned too")
+        } else {
+            None
+        });
+        let option_tokens = |message: Option<&str>| match message {
+            Some(message) => quote! { ::core::option::Option::Some(#message) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let (error, by_ref_error) = (option_tokens(error), option_tokens(by_ref_error));
+        let build_error_type = match build_fn_error {
+            Some(build_fn_error) => quote! { #build_fn_error },
+            None => quote! { #builder_error_ident },
+        };
+        // The builders refused above still get the hooks, for their parents to only fail on the
+        // check. __sub_builder_build_ref() builds by reference, for parents that aren't owned.
+        let result_type = quote! {
+            ::core::result::Result<#derive_struct_ident #ty_generics, #build_error_type>
+        };
+        let unreachable = quote! {
+            ::core::unreachable!("rejected by __sub_builder_error()")
+        };
+        let build_hook = if typestate || *build_fn_skip {
+            quote! {
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_build(&self) -> #result_type {
+                    #unreachable
+                }
+            }
+        } else {
+            quote! {
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_build(#build_receiver) -> #result_type #build_where_clause {
+                    self.#build_fn_name()
+                }
+            }
+        };
+        let build_ref_hook = if typestate || *build_fn_skip || pattern == BuilderPattern::Owned {
+            quote! {
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_build_ref(&self) -> #result_type {
+                    #unreachable
+                }
+            }
+        } else {
+            quote! {
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_build_ref(&self) -> #result_type #build_where_clause {
+                    self.#build_fn_name()
+                }
+            }
+        };
+        let (from_hook, merge_hook) = if typestate {
+            (
+                quote! {
+                    let _ = value;
+                    #unreachable
+                },
+                quote! {
+                    let _ = (base, over);
+                    #unreachable
+                },
+            )
+        } else if pattern == BuilderPattern::Mutable {
+            (
+                quote! { ::core::convert::From::from(value) },
+                quote! {
+                    let mut base = base;
+                    base.merge(over);
+                    base
+                },
+            )
+        } else {
+            (
+                quote! { ::core::convert::From::from(value) },
+                quote! { base.merge(over) },
+            )
+        };
+        quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #derive_struct_ident #ty_generics #where_clause {
+                #[doc(hidden)]
+                #builder_vis const fn __sub_builder_error(by_ref: bool) -> ::core::option::Option<&'static str> {
+                    if by_ref {
+                        #by_ref_error
+                    } else {
+                        #error
+                    }
+                }
+            }
+
+            #[allow(dead_code)]
+            impl #impl_generics #unset_builder_type #where_clause {
+                #build_hook
+
+                #build_ref_hook
+
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_from(value: #derive_struct_ident #ty_generics) -> Self {
+                    #from_hook
+                }
+
+                #[doc(hidden)]
+                #builder_vis fn __sub_builder_merge(base: Self, over: Self) -> Self {
+                    #merge_hook
+                }
+            }
+        }
+    };
+    let sub_builder_checks = helpers
+        .iter()
+        .map(|h| h.field_sub_builder_check_form(pattern));
+
+    // Error returned by build(), one variant per failure kind: (variant, Display match arm).
+    let mut builder_error_variants = vec![(
+        quote! { MissingField(&'static str) },
+        quote! { #builder_error_ident::MissingField(field) => ::core::write!(f, "missing field `{}`", field) },
+    )];
+    if helpers.iter().any(|h| h.is_sub_builder()) {
+        builder_error_variants.push((
+            quote! {
+                SubBuilderError(
+                    &'static str,
+                    #alloc_crate::boxed::Box<dyn ::core::error::Error + ::core::marker::Send + ::core::marker::Sync>,
+                )
+            },
+            quote! { #builder_error_ident::SubBuilderError(field, error) => ::core::write!(f, "{}: {}", field, error) },
+        ));
+    }
+    if validate_fn.is_some() {
+        builder_error_variants.push((
+            quote! { ValidationError(#alloc_crate::string::String) },
+            quote! { #builder_error_ident::ValidationError(message) => ::core::write!(f, "{}", message) },
+        ));
+    }
+    if helpers.iter().any(|h| h.has_env_var()) {
+        builder_error_variants.push((
+            quote! { EnvError(&'static str, ::std::string::String) },
+            quote! {
+                #builder_error_ident::EnvError(env_var, message) =>
+                    ::core::write!(f, "environment variable `{}`: {}", env_var, message)
+            },
+        ));
+    }
+    if *from_args {
+        builder_error_variants.push((
+            quote! { ArgsError(::std::string::String) },
+            quote! { #builder_error_ident::ArgsError(message) => ::core::write!(f, "{}", message) },
+        ));
+        builder_error_variants.push((
+            quote! { Help(&'static str) },
+            quote! { #builder_error_ident::Help(help) => ::core::write!(f, "{}", help) },
+        ));
+    }
+    // Errors of nested builders are exposed as the source of SubBuilderError.
+    let error_source_method = if helpers.iter().any(|h| h.is_sub_builder()) {
+        quote! {
+            fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                match self {
+                    #builder_error_ident::SubBuilderError(_, error) => ::core::option::Option::Some(&**error),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let (error_variants, error_display_arms): (Vec<_>, Vec<_>) =
+        builder_error_variants.into_iter().unzip();
+    let builder_error_block = quote! {
+        #[derive(Debug)]
+        #builder_vis enum #builder_error_ident {
+            #(#error_variants,)*
+        }
+
+        impl ::core::fmt::Display for #builder_error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#error_display_arms,)*
+                }
+            }
+        }
+
+        impl ::core::error::Error for #builder_error_ident {
+            #error_source_method
         }
     };
 
     let builder_field_each_setter = helpers
         .iter()
-        .map(|h: &BuilderMacroFieldHelper<'_>| h.field_setter_each_form());
+        .map(|h: &BuilderMacroFieldHelper<'_>| h.field_setter_each_form(pattern));
+
+    // Layering partial builders: merge() lets fields set in `other` win, or() only fills the
+    // fields still unset from `fallback`.
+    let merge_ident = format_ident!("merge");
+    let builder_merge_method = pattern.setter_method(
+        builder_vis,
+        &merge_ident,
+        &Generics::default(),
+        quote! { other: Self },
+        |builder| {
+            let fields = helpers.iter().map(|h| {
+                let name = format_ident!("{}", h.field_name());
+                h.field_merge_form(
+                    &builder,
+                    quote! { #builder.#name.take() },
+                    quote! { other.#name },
+                )
+            });
+            quote! { #(#fields)* }
+        },
+    );
+    let or_ident = format_ident!("or");
+    let builder_or_method = pattern.setter_method(
+        builder_vis,
+        &or_ident,
+        &Generics::default(),
+        quote! { fallback: Self },
+        |builder| {
+            let fields = helpers.iter().map(|h| {
+                let name = format_ident!("{}", h.field_name());
+                h.field_merge_form(
+                    &builder,
+                    quote! { fallback.#name },
+                    quote! { #builder.#name.take() },
+                )
+            });
+            quote! { #(#fields)* }
+        },
+    );
+
+    // Fields with #[builder(env = "...")] are filled from the environment, on top of the fields
+    // set so far: from_env() starts from an empty builder.
+    let builder_env_methods = if helpers.iter().any(|h| h.has_env_var()) {
+        let fill_ident = format_ident!("fill_from_env");
+        let fill_method = pattern.try_setter_method(
+            builder_vis,
+            &fill_ident,
+            &Generics::default(),
+            quote! {},
+            quote! { #builder_error_ident },
+            |builder| {
+                let fields = helpers
+                    .iter()
+                    .map(|h| h.field_env_form(&builder, &builder_error_ident));
+                quote! { #(#fields)* }
+            },
+        );
+        let turbofish = ty_generics.as_turbofish();
+        let new_builder = quote! { #derive_struct_ident #turbofish::#constructor_ident() };
+        let from_env_body = if pattern == BuilderPattern::Mutable {
+            quote! {
+                let mut builder = #new_builder;
+                builder.fill_from_env()?;
+                ::core::result::Result::Ok(builder)
+            }
+        } else {
+            quote! { #new_builder.fill_from_env() }
+        };
+        quote! {
+            #fill_method
+
+            #builder_vis fn from_env() -> ::core::result::Result<Self, #builder_error_ident> {
+                #from_env_body
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Inspecting the builder before build(): missing_fields() lists the unset fields build()
+    // would fail on, nested ones as `server.port`, each field gets is_set_, get_ and unset_
+    // accessors, and clear() resets it. Typestate builders only get the accessors that leave
+    // their type alone.
+    let builder_introspection_inner = helpers
+        .iter()
+        .map(|h| h.field_introspection_form(pattern, typestate));
+    let path = quote! { path };
+    let builder_missing_inner = helpers.iter().map(|h| h.field_missing_form(&path));
+    let clear_ident = format_ident!("clear");
+    let builder_clear_method = if typestate {
+        quote! {}
+    } else {
+        pattern.setter_method(
+            builder_vis,
+            &clear_ident,
+            &Generics::default(),
+            quote! {},
+            |builder| {
+                let fields = helpers.iter().map(|h| h.field_unset_form(&builder));
+                quote! { #(#fields)* }
+            },
+        )
+    };
+    let builder_introspection_methods = quote! {
+        #builder_vis fn missing_fields(&self) -> impl ::core::iter::Iterator<Item = &'static str> + '_ {
+            let paths = <#derive_struct_ident #ty_generics>::#paths_ident;
+            ::core::iter::Iterator::filter(paths.split('\0'), move |path| self.__is_missing(path))
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_variables)]
+        #builder_vis fn __is_missing(&self, #path: &str) -> bool {
+            #(#builder_missing_inner)*
+            false
+        }
 
-    let builder_implementation_block = quote! {
-        impl #builder_struct_ident {
+        #(#builder_introspection_inner)*
 
-            #(#builder_field_setter)*
+        #builder_clear_method
+    };
+
+    // #[builder(from_args)]: `--field-name value` or `--field-name=value` for each field, bool
+    // switches set by their flag alone, `--help` failing with the help text built from the doc
+    // comments. Fields whose flag isn't given stay unset, so defaults and merge() still apply.
+    let builder_from_args_method = if *from_args {
+        let mut help_lines = helpers
+            .iter()
+            .filter_map(|h| h.field_args_help())
+            .collect::<Vec<_>>();
+        if !helpers.iter().any(|h| h.args_flag() == "--help") {
+            help_lines.push(("--help".to_owned(), "Print this help.".to_owned()));
+        }
+        let width = help_lines
+            .iter()
+            .map(|(usage, _)| usage.len())
+            .max()
+            .unwrap_or(0);
+        let mut help = String::new();
+        if !target.doc.is_empty() {
+            help.push_str(&format!("{}\n\n", target.doc));
+        }
+        help.push_str("Options:");
+        for (usage, description) in &help_lines {
+            let line = format!("  {:width$}  {}", usage, description, width = width);
+            help.push_str(&format!("\n{}", line.trim_end()));
+        }
+        let builder = quote! { __builder };
+        let flag_arms = helpers
+            .iter()
+            .map(|h| h.field_args_form(&builder, &builder_error_ident))
+            .collect::<syn::Result<Vec<_>>>()?;
+        let turbofish = ty_generics.as_turbofish();
+        quote! {
+            #builder_vis fn from_args<ARGS: ::core::iter::IntoIterator<Item = ::std::string::String>>(
+                args: ARGS,
+            ) -> ::core::result::Result<Self, #builder_error_ident> {
+                let mut #builder = #derive_struct_ident #turbofish::#constructor_ident();
+                let mut __args = ::core::iter::IntoIterator::into_iter(args);
+                while let ::core::option::Option::Some(__arg) = ::core::iter::Iterator::next(&mut __args) {
+                    let (__flag, __inline) = match __arg.find('=') {
+                        ::core::option::Option::Some(index) if __arg.starts_with("--") => (
+                            ::std::borrow::ToOwned::to_owned(&__arg[..index]),
+                            ::core::option::Option::Some(::std::borrow::ToOwned::to_owned(&__arg[index + 1..])),
+                        ),
+                        _ => (__arg, ::core::option::Option::None),
+                    };
+                    match __flag.as_str() {
+                        #(#flag_arms)*
+                        // A field named `help` takes the flag over.
+                        #[allow(unreachable_patterns)]
+                        "--help" => return ::core::result::Result::Err(#builder_error_ident::Help(#help)),
+                        _ => {
+                            return ::core::result::Result::Err(#builder_error_ident::ArgsError(
+                                ::std::format!("unexpected argument `{}`", __flag),
+                            ));
+                        }
+                    }
+                }
+                ::core::result::Result::Ok(#builder)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let builder_implementation_block = if typestate {
+        // One impl block per required setter: generic over every other field's state, only
+        // callable while this field is unset, returning the builder with the field set.
+        let typestate_setter_blocks = required_helpers.iter().map(|current| {
+            let mut setter_generics = struct_generics.clone();
+            for h in &required_helpers {
+                if h.field_name() != current.field_name() {
+                    let param = h.typestate_param_ident();
+                    setter_generics
+                        .params
+                        .push(parse_quote!(const #param: bool));
+                }
+            }
+            let (setter_impl_generics, _, _) = setter_generics.split_for_impl();
+            let states_with = |state: proc_macro2::TokenStream| {
+                required_helpers
+                    .iter()
+                    .map(|h| {
+                        if h.field_name() == current.field_name() {
+                            state.clone()
+                        } else {
+                            h.typestate_param_ident().to_token_stream()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let self_type = builder_type_with_states(&states_with(quote! { false }));
+            let return_type = builder_type_with_states(&states_with(quote! { true }));
+            let setter = current.field_typestate_setter_form(
+                &helpers,
+                builder_struct_ident,
+                &return_type,
+                &marker_init,
+            );
+            quote! {
+                impl #setter_impl_generics #self_type #where_clause {
+                    #setter
+                }
+            }
+        });
+        quote! {
+            impl #builder_impl_generics #builder_struct_ident #builder_ty_generics #where_clause {
 
-            #(#builder_field_each_setter)*
+                #(#builder_field_setter)*
 
-            #builder_build_method
+                #(#builder_field_each_setter)*
 
+                #builder_introspection_methods
+
+            }
+
+            #(#typestate_setter_blocks)*
+
+            impl #impl_generics #set_builder_type #where_clause {
+
+                #builder_build_method
+
+            }
+        }
+    } else {
+        quote! {
+            impl #impl_generics #builder_struct_ident #ty_generics #where_clause {
+
+                #(#builder_field_setter)*
+
+                #(#builder_field_each_setter)*
+
+                #builder_merge_method
+
+                #builder_or_method
+
+                #builder_introspection_methods
+
+                #builder_env_methods
+
+                #builder_from_args_method
+
+                #builder_build_method
+
+            }
         }
     };
 
@@ -74,9 +1295,17 @@ pub fn derive(input: TokenStream) -> TokenStream {
     let expanded = quote! {
         #builder_definition_block
 
+        #builder_error_block
+
         #builder_implementation_block
 
         #builder_constructor_block
+
+        #builder_from_value_block
+
+        #sub_builder_hooks_block
+
+        #(#sub_builder_checks)*
     };
-    expanded.into()
+    Ok(expanded)
 }
@@ -1,16 +1,55 @@
 use std::collections::HashMap;
 
 use quote::quote;
-use quote::{format_ident, ToTokens};
-use syn::{Data, Field, GenericArgument, LitStr, PathArguments, Type};
+use quote::{format_ident, quote_spanned, ToTokens};
+use syn::{
+    meta::ParseNestedMeta, parenthesized, parse_quote, Attribute, Expr, Field, GenericArgument,
+    GenericParam, Generics, Ident, Index, Lit, Member, PathArguments, Token, Type, Visibility,
+};
 
 // Helper struct, help generating code pieces.
 pub struct BuilderMacroFieldHelper<'a> {
+    // Name of the builder field and its setter: the field's own name, `_0` for the first field
+    // of a tuple struct, or #[builder(name = "...")].
     field_name: String,
+    // The field in the derived struct, `executable` or `0`.
+    member: Member,
+    // The field's name, or type for tuple fields, for errors about the field as a whole.
+    span: proc_macro2::Span,
     field_type: &'a Type,
     is_option_type: bool,
     is_vec_type: bool,
-    field_attributes: HashMap<String, std::option::Option<String>>,
+    field_attributes: HashMap<String, std::option::Option<AttrValue>>,
+    // Value used by build() when the field is unset, from #[builder(default)] or the struct's Default.
+    default_value: std::option::Option<proc_macro2::TokenStream>,
+    // #[builder(each = "...")] or #[builder(each(name = "..."))]: the one-at-a-time setter.
+    each_name: std::option::Option<Ident>,
+    // #[builder(setter(into))]: setters accept `impl Into<T>`.
+    setter_into: bool,
+    // #[builder(setter(strip_option))]: setters of Option<T> fields accept T, on by default.
+    strip_option: bool,
+    // #[builder(try_setter)]: also generate try_field setters accepting `TryInto<T>`.
+    try_setter: bool,
+    // #[builder(vis = "...")] on the field, or the builder's visibility.
+    setter_vis: Visibility,
+    // #[builder(sub_builder)]: the field's own builder type, `ServerBuilder` next to `Server`, or
+    // #[builder(sub_builder(builder = "config::ServerBuilder"))].
+    sub_builder_type: std::option::Option<Type>,
+    // The field type mentions a generic parameter of the struct, which items outside the
+    // struct's impls can't name.
+    uses_struct_params: bool,
+    // #[builder(skip)] or #[builder(compute = "...")]: not part of the builder, build() uses
+    // default_value or compute_expr.
+    skipped: bool,
+    // #[builder(compute = "expr")]: evaluated by build() with the other fields in scope, or
+    // #[builder(compute = "path::to::fn")] called with the builder's fields.
+    compute_expr: std::option::Option<Expr>,
+    // #[builder(field_attr(...))]: attributes of the builder's field.
+    builder_field_attrs: Vec<syn::Meta>,
+    // #[builder(env = "APP_PORT")]: variable read by fill_from_env().
+    env_var: std::option::Option<String>,
+    // The field's doc comment, describing its flag in the from_args() help.
+    doc: String,
 }
 
 // Implementation of the helper.
@@ -22,13 +61,23 @@ impl<'a> BuilderMacroFieldHelper<'a> {
     pub fn field_defintion_inner_form(&self) -> proc_macro2::TokenStream {
         let name = format_ident!("{}", self.field_name);
         let ty = self.field_type;
-        if self.is_option_type {
+        let attrs = &self.builder_field_attrs;
+        if self.skipped {
+            quote! {}
+        } else if let Some(sub_builder_type) = &self.sub_builder_type {
             quote! {
+                #(#[#attrs])*
+                #name: ::core::option::Option<#sub_builder_type>,
+            }
+        } else if self.is_option_type {
+            quote! {
+                #(#[#attrs])*
                 #name: #ty, // Option<Option<T>> is not needed
             }
         } else {
             quote! {
-                #name: std::option::Option<#ty>,
+                #(#[#attrs])*
+                #name: ::core::option::Option<#ty>,
             }
         }
     }
@@ -41,36 +90,199 @@ impl<'a> BuilderMacroFieldHelper<'a> {
     //    }
     pub fn field_construction_inner_form(&self) -> proc_macro2::TokenStream {
         let name = format_ident!("{}", self.field_name);
-        if self.is_vec_type {
+        if self.skipped {
+            return quote! {};
+        }
+        quote! {
+            #name: ::core::option::Option::None,
+        }
+    }
+
+    // code block in From<Command> for CommandBuilder:
+    //    fn from(value: Command) -> CommandBuilder {
+    //         CommandBuilder {
+    //             $$$$$ <- generate these
+    //         }
+    //    }
+    pub fn field_from_value_form(&self) -> proc_macro2::TokenStream {
+        let name = format_ident!("{}", self.field_name);
+        let member = &self.member;
+        if self.skipped {
+            quote! {}
+        } else if let Some(sub_builder_type) = &self.sub_builder_type {
             quote! {
-                #name: std::option::Option::Some(Vec::new()),
+                #name: ::core::option::Option::Some(<#sub_builder_type>::__sub_builder_from(value.#member)),
+            }
+        } else if self.is_option_type {
+            quote! {
+                #name: value.#member,
             }
         } else {
             quote! {
-                #name: std::option::Option::None,
+                #name: ::core::option::Option::Some(value.#member),
             }
         }
     }
 
     // code block of chained setter methods
-    pub fn field_setter_from(&self) -> proc_macro2::TokenStream {
-        let name = format_ident!("{}", self.field_name);
-        let ty = self.field_type;
-        if self.is_option_type {
-            let inner_ty = extract_generic_type(ty);
-            quote! {
-                fn #name(&mut self, #name:#inner_ty) -> &mut Self {
-                    self.#name = std::option::Option::Some(#name);
-                    self
-                }
+    // Skipped when the one-at-a-time `each` setter has the same name as the field.
+    pub fn field_setter_from(&self, pattern: BuilderPattern) -> proc_macro2::TokenStream {
+        if self.skipped
+            || self
+                .each_name
+                .as_ref()
+                .is_some_and(|each_name| *each_name == self.field_name)
+        {
+            return quote! {};
+        }
+        if let Some(sub_builder_type) = &self.sub_builder_type {
+            let name = format_ident!("{}", self.field_name);
+            let ty = self.field_type;
+            let vis = &self.setter_vis;
+            // Accessor to the nested builder, created on first use.
+            if pattern == BuilderPattern::Mutable {
+                return quote! {
+                    #vis fn #name(&mut self) -> &mut #sub_builder_type {
+                        self.#name.get_or_insert_with(<#ty>::builder)
+                    }
+                };
             }
+            // Owned and immutable builders hand out no reference, they take the nested builder.
+            return pattern.setter_method(
+                vis,
+                &name,
+                &Generics::default(),
+                quote! { #name: #sub_builder_type },
+                |builder| quote! { #builder.#name = ::core::option::Option::Some(#name); },
+            );
+        }
+        let name = format_ident!("{}", self.field_name);
+        let (arg_ty, stored_value) = self.setter_argument();
+        let args = quote! { #name:#arg_ty };
+        let setter = pattern.setter_method(
+            &self.setter_vis,
+            &name,
+            &Generics::default(),
+            args,
+            |builder| {
+                quote! { #builder.#name = #stored_value; }
+            },
+        );
+        if !self.try_setter {
+            return setter;
+        }
+        // try_port<VALUE: TryInto<u16>>(&mut self, port: VALUE) -> Result<&mut Self, VALUE::Error>
+        let method_name = format_ident!("try_{}", self.field_name);
+        let value_ty = self.setter_value_type();
+        let generics = parse_quote! { <VALUE: ::core::convert::TryInto<#value_ty>> };
+        let stored_value =
+            self.setter_stored_value(quote! { ::core::convert::TryInto::try_into(#name)? });
+        let try_setter = pattern.try_setter_method(
+            &self.setter_vis,
+            &method_name,
+            &generics,
+            quote! { #name: VALUE },
+            quote! { <VALUE as ::core::convert::TryInto<#value_ty>>::Error },
+            |builder| quote! { #builder.#name = #stored_value; },
+        );
+        quote! {
+            #setter
+            #try_setter
+        }
+    }
+
+    // Type of the value given to setters, T for strip_option Option<T> fields.
+    fn setter_value_type(&self) -> proc_macro2::TokenStream {
+        if self.is_option_type && self.strip_option {
+            extract_generic_type(self.field_type).to_token_stream()
         } else {
-            quote! {
-                fn #name(&mut self, #name:#ty) -> &mut Self {
-                    self.#name = std::option::Option::Some(#name);
-                    self
+            self.field_type.to_token_stream()
+        }
+    }
+
+    // Builder field value storing the given setter value.
+    fn setter_stored_value(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.is_option_type && !self.strip_option {
+            // Option<T> is stored as is.
+            value
+        } else {
+            quote! { ::core::option::Option::Some(#value) }
+        }
+    }
+
+    // Setter argument type and the value stored from it, according to setter(into, strip_option).
+    fn setter_argument(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let name = format_ident!("{}", self.field_name);
+        let value_ty = self.setter_value_type();
+        let (arg_ty, value) = if self.setter_into {
+            (
+                quote! { impl ::core::convert::Into<#value_ty> },
+                quote! { #name.into() },
+            )
+        } else {
+            (value_ty, quote! { #name })
+        };
+        (arg_ty, self.setter_stored_value(value))
+    }
+
+    // Typestate setter of a required field, moving every field into a builder of the "set" state:
+    // fn executable(self, executable: String) -> CommandBuilder<true, ...> {
+    //     CommandBuilder {
+    //         $$$$$ <- generate these
+    //     }
+    // }
+    pub fn field_typestate_setter_form(
+        &self,
+        helpers: &[BuilderMacroFieldHelper],
+        builder_ident: &Ident,
+        return_ty: &proc_macro2::TokenStream,
+        marker_init: &proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let name = format_ident!("{}", self.field_name);
+        let vis = &self.setter_vis;
+        let moved_fields = |stored_value: proc_macro2::TokenStream| {
+            helpers
+                .iter()
+                .filter(|h| !h.skipped)
+                .map(|h| {
+                    let field = format_ident!("{}", h.field_name);
+                    if h.field_name == self.field_name {
+                        quote! { #field: #stored_value, }
+                    } else {
+                        quote! { #field: self.#field, }
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let (arg_ty, stored_value) = self.setter_argument();
+        let setter_fields = moved_fields(stored_value);
+        let setter = quote! {
+            #vis fn #name(self, #name:#arg_ty) -> #return_ty {
+                #builder_ident {
+                    #(#setter_fields)*
+                    #marker_init
                 }
             }
+        };
+        if !self.try_setter {
+            return setter;
+        }
+        let method_name = format_ident!("try_{}", self.field_name);
+        let value_ty = self.setter_value_type();
+        let try_setter_fields = moved_fields(
+            self.setter_stored_value(quote! { ::core::convert::TryInto::try_into(#name)? }),
+        );
+        quote! {
+            #setter
+
+            #vis fn #method_name<VALUE: ::core::convert::TryInto<#value_ty>>(self, #name: VALUE)
+                -> ::core::result::Result<#return_ty, <VALUE as ::core::convert::TryInto<#value_ty>>::Error>
+            {
+                ::core::result::Result::Ok(#builder_ident {
+                    #(#try_setter_fields)*
+                    #marker_init
+                })
+            }
         }
     }
 
@@ -80,145 +292,1629 @@ impl<'a> BuilderMacroFieldHelper<'a> {
     //         $$$$$ <- generate these
     //     }
     // }
-    pub fn field_build_inner_form(&self) -> proc_macro2::TokenStream {
+    pub fn field_build_inner_form(
+        &self,
+        error_ident: &Ident,
+        pattern: BuilderPattern,
+    ) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        let value = self.field_build_value_form(error_ident, pattern);
+        quote! {
+            #member: #value,
+        }
+    }
+
+    // Value of the field built from the builder, its default when unset.
+    // The owned pattern moves the values out of the builder instead of cloning them.
+    fn field_build_value_form(
+        &self,
+        error_ident: &Ident,
+        pattern: BuilderPattern,
+    ) -> proc_macro2::TokenStream {
         let name = format_ident!("{}", self.field_name);
-        if self.is_option_type {
+        let field_name = self.display_name();
+        let value = if pattern == BuilderPattern::Owned {
+            quote! { self.#name }
+        } else {
+            quote! { self.#name.clone() }
+        };
+        if self.skipped {
+            return self.default_value.to_token_stream();
+        }
+        if self.sub_builder_type.is_some() {
+            return self.field_sub_builder_build_form(error_ident, pattern);
+        }
+        match (&self.default_value, self.is_option_type) {
+            (std::option::Option::Some(default_value), true) => quote! {
+                match #value {
+                    ::core::option::Option::Some(value) => ::core::option::Option::Some(value),
+                    ::core::option::Option::None => #default_value,
+                }
+            },
+            (std::option::Option::Some(default_value), false) => quote! {
+                match #value {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default_value,
+                }
+            },
+            (std::option::Option::None, true) => quote! {
+                #value
+            },
+            (std::option::Option::None, false) if self.is_collection() => quote! {
+                #value.unwrap_or_default()
+            },
+            (std::option::Option::None, false) => quote! {
+                #value.ok_or(#error_ident::MissingField(#field_name))?
+            },
+        }
+    }
+
+    // With computed fields, build() first binds every other field to a local:
+    //     let __field_host = ...;
+    pub fn field_build_local_form(
+        &self,
+        error_ident: &Ident,
+        pattern: BuilderPattern,
+    ) -> proc_macro2::TokenStream {
+        if self.is_computed() {
+            return quote! {};
+        }
+        let local = self.build_local_ident();
+        let value = self.field_build_value_form(error_ident, pattern);
+        quote! {
+            let #local = #value;
+        }
+    }
+
+    // then evaluates the computed ones in declaration order, each seeing the fields built before
+    // it by reference under their own names:
+    //     let __field_url = {
+    //         let host = &__field_host;
+    //         let port = &__field_port;
+    //         format!("http://{}:{}", host, port)
+    //     };
+    // A path other than a field's name is a function called with the fields set in the builder:
+    //     let __field_url = urls::make_url(&__field_host, &__field_port);
+    pub fn field_compute_form(
+        &self,
+        in_scope: &[&BuilderMacroFieldHelper],
+    ) -> proc_macro2::TokenStream {
+        let Some(compute_expr) = &self.compute_expr else {
+            return quote! {};
+        };
+        let local = self.build_local_ident();
+        let is_fn_path = matches!(compute_expr, Expr::Path(expr_path)
+            if expr_path.qself.is_none()
+                && !in_scope.iter().any(|h| expr_path.path.is_ident(&h.value_ident())));
+        if is_fn_path {
+            let args = in_scope.iter().filter(|h| !h.skipped).map(|h| {
+                let local = h.build_local_ident();
+                quote! { &#local }
+            });
+            return quote! {
+                let #local = #compute_expr(#(#args),*);
+            };
+        }
+        let bindings = in_scope.iter().map(|h| {
+            let name = h.value_ident();
+            let local = h.build_local_ident();
             quote! {
-                #name: self.#name.clone(),
+                #[allow(unused_variables)]
+                let #name = &#local;
             }
+        });
+        quote! {
+            let #local = {
+                #(#bindings)*
+                #compute_expr
+            };
+        }
+    }
+
+    // and moves the locals into the value:
+    //     Server { host: __field_host, ... }
+    pub fn field_build_member_form(&self) -> proc_macro2::TokenStream {
+        let member = &self.member;
+        let local = self.build_local_ident();
+        quote! {
+            #member: #local,
+        }
+    }
+
+    // Local holding the built field in build(), e.g. `__field_port`.
+    fn build_local_ident(&self) -> Ident {
+        format_ident!("__field_{}", self.field_name.trim_start_matches("r#"))
+    }
+
+    // Name of the field in compute expressions: its own name, or the builder's for tuple fields.
+    fn value_ident(&self) -> Ident {
+        match &self.member {
+            Member::Named(ident) => ident.clone(),
+            Member::Unnamed(_) => format_ident!("{}", self.field_name),
+        }
+    }
+
+    // Build the nested builder, an untouched one being built empty unless the field has a default.
+    fn field_sub_builder_build_form(
+        &self,
+        error_ident: &Ident,
+        pattern: BuilderPattern,
+    ) -> proc_macro2::TokenStream {
+        let name = format_ident!("{}", self.field_name);
+        let field_name = self.display_name();
+        let ty = self.field_type;
+        let (sub_builder, build_hook) = if pattern == BuilderPattern::Owned {
+            (quote! { self.#name }, quote! { __sub_builder_build })
         } else {
-            quote! {
-                #name: self.#name.clone().unwrap(),
+            (quote! { &self.#name }, quote! { __sub_builder_build_ref })
+        };
+        // Generic nested types are checked when build() is instantiated instead.
+        let check = if self.uses_struct_params {
+            let check = self.sub_builder_check(pattern);
+            quote! { const { #check }; }
+        } else {
+            quote! {}
+        };
+        let map_error = quote! {
+            map_err(|error| #error_ident::SubBuilderError(#field_name, ::core::convert::From::from(error)))
+        };
+        let unset_value = match &self.default_value {
+            Some(default_value) => default_value.clone(),
+            None => quote! { <#ty>::builder().#build_hook().#map_error? },
+        };
+        quote! {{
+            #check
+            match #sub_builder {
+                ::core::option::Option::Some(builder) => builder.#build_hook().#map_error?,
+                ::core::option::Option::None => #unset_value,
+            }
+        }}
+    }
+
+    // Nested builders this builder's build() can't use fail to compile, with the reason given by
+    // the nested derive:
+    //     const _: () = if let Some(message) = <Server>::__sub_builder_error(true) {
+    //         panic!("{}", message)
+    //     };
+    // `true` for a builder building by reference, which can't move an owned nested builder.
+    pub fn field_sub_builder_check_form(
+        &self,
+        pattern: BuilderPattern,
+    ) -> proc_macro2::TokenStream {
+        if !self.is_sub_builder() || self.uses_struct_params {
+            return quote! {};
+        }
+        let check = self.sub_builder_check(pattern);
+        quote_spanned! {syn::spanned::Spanned::span(self.field_type)=>
+            const _: () = #check;
+        }
+    }
+
+    fn sub_builder_check(&self, pattern: BuilderPattern) -> proc_macro2::TokenStream {
+        let ty = self.field_type;
+        let by_ref = pattern != BuilderPattern::Owned;
+        quote_spanned! {syn::spanned::Spanned::span(ty)=>
+            if let ::core::option::Option::Some(message) = <#ty>::__sub_builder_error(#by_ref) {
+                ::core::panic!("{}", message)
             }
         }
     }
 
-    // Only make sense if the type is Vec<T>
-    pub fn field_setter_each_form(&self) -> proc_macro2::TokenStream {
+    pub fn is_computed(&self) -> bool {
+        self.compute_expr.is_some()
+    }
+
+    pub fn field_type(&self) -> &Type {
+        self.field_type
+    }
+
+    // The builder field's type mentions the generic parameter.
+    pub fn uses_param(&self, param: &Ident) -> bool {
+        !self.skipped && type_uses_params(self.field_type.to_token_stream(), &[param])
+    }
+
+    pub fn is_sub_builder(&self) -> bool {
+        self.sub_builder_type.is_some()
+    }
+
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    // Name of the field in errors and missing_fields(), `type` for `r#type`.
+    pub fn display_name(&self) -> &str {
+        self.field_name.trim_start_matches("r#")
+    }
+
+    // Builder methods generated for the field, with what they are and the option renaming them:
+    //     ("port", "setter", "name"), ("try_port", "setter", "name"), ("is_set_port", ...
+    pub fn method_names(&self, typestate: bool) -> Vec<(String, &'static str, &'static str)> {
+        let mut names = Vec::new();
+        if self.skipped {
+            return names;
+        }
+        let suffix = self.field_name.trim_start_matches("r#");
+        let each_name = self
+            .each_name
+            .as_ref()
+            .map(|each_name| each_name.to_string().trim_start_matches("r#").to_owned());
+        if each_name.as_deref() != Some(suffix) {
+            names.push((suffix.to_owned(), "setter", "name"));
+            if self.try_setter && !self.is_sub_builder() {
+                names.push((format!("try_{}", suffix), "setter", "name"));
+            }
+        }
+        if let Some(each_name) = each_name {
+            names.push((each_name, "setter", "each"));
+        }
+        names.push((format!("is_set_{}", suffix), "accessor", "name"));
+        names.push((format!("get_{}", suffix), "accessor", "name"));
+        if !typestate {
+            names.push((format!("unset_{}", suffix), "accessor", "name"));
+        }
+        names
+    }
+
+    pub fn span(&self) -> proc_macro2::Span {
+        self.span
+    }
+
+    // Field must be set before build(): not skipped, neither Option<T> nor a collection, and
+    // without a default.
+    pub fn is_required(&self) -> bool {
+        !self.skipped
+            && !self.is_option_type
+            && !self.is_collection()
+            && !self.is_sub_builder()
+            && self.default_value.is_none()
+    }
+
+    // Vec<T> or a collection with an `each` setter, empty when unset.
+    pub fn is_collection(&self) -> bool {
+        self.is_vec_type || self.each_name.is_some()
+    }
+
+    // Const parameter tracking whether a required field is set, e.g. `__CURRENT_DIR_IS_SET`.
+    pub fn typestate_param_ident(&self) -> Ident {
+        format_ident!(
+            "__{}_IS_SET",
+            self.field_name.trim_start_matches("r#").to_uppercase()
+        )
+    }
+
+    // Predicate `T: Clone` for build(), only needed when the field type mentions a type parameter.
+    pub fn field_clone_bound(
+        &self,
+        type_params: &[&Ident],
+    ) -> std::option::Option<proc_macro2::TokenStream> {
+        let ty = self.field_type;
+        if !self.skipped
+            && !self.is_sub_builder()
+            && type_uses_params(ty.to_token_stream(), type_params)
+        {
+            std::option::Option::Some(quote! { #ty: ::core::clone::Clone })
+        } else {
+            std::option::Option::None
+        }
+    }
+
+    // Predicate `T: Default` for build() when a generic field type falls back to Default::default().
+    pub fn field_default_bound(
+        &self,
+        type_params: &[&Ident],
+    ) -> std::option::Option<proc_macro2::TokenStream> {
+        let ty = self.field_type;
+        let uses_default_trait = matches!(self.field_attributes.get("default"), Some(None))
+            || (self.default_value.is_none() && self.is_collection())
+            || (self.skipped
+                && !self.is_computed()
+                && !self.field_attributes.contains_key("default"));
+        if uses_default_trait && type_uses_params(ty.to_token_stream(), type_params) {
+            std::option::Option::Some(quote! { #ty: ::core::default::Default })
+        } else {
+            std::option::Option::None
+        }
+    }
+
+    // One-at-a-time setter from #[builder(each = "...")] or #[builder(each(name = "...", into))],
+    // for any collection implementing Extend and Default:
+    //   - maps take a key and a value,
+    //   - known std collections take their element type,
+    //   - other types take any VALUE they can be extended with.
+    pub fn field_setter_each_form(&self, pattern: BuilderPattern) -> proc_macro2::TokenStream {
         let name = format_ident!("{}", self.field_name);
         let ty = self.field_type;
-        if self.is_vec_type {
-            let inner_ty = extract_generic_type(ty);
-            if let Some(value) = self.field_attributes.get("each") {
-                let each_conf_value = value.as_ref().unwrap();
-                if each_conf_value == &self.field_name {
-                    return quote! {};
-                } else {
-                    // generate fn accroding to each's configuration value.
-                    let method_name = format_ident!("{}", each_conf_value);
-                    quote! {
-                        fn #method_name(&mut self, value: #inner_ty) -> &mut Self {
-                            self.#name.as_mut().unwrap().push(value);
-                            self
-                        }
+        let method_name = match &self.each_name {
+            Some(each_name) if !self.skipped => each_name,
+            _ => {
+                return quote! {
+                    // Builder each attribute process skipped: no configured value
+                };
+            }
+        };
+        let into = flag_attribute(&self.field_attributes, "each.into").unwrap_or(false);
+        let into_type = |ty: &Type| {
+            if into {
+                quote! { impl ::core::convert::Into<#ty> }
+            } else {
+                ty.to_token_stream()
+            }
+        };
+        let into_value = |value: proc_macro2::TokenStream| {
+            if into {
+                quote! { #value.into() }
+            } else {
+                value
+            }
+        };
+        let (generics, args, item) = match collection_kind(ty) {
+            CollectionKind::Map(key_ty, value_ty) => {
+                let (key_ty, value_ty) = (into_type(key_ty), into_type(value_ty));
+                let (key, value) = (into_value(quote! { key }), into_value(quote! { value }));
+                (
+                    Generics::default(),
+                    quote! { key: #key_ty, value: #value_ty },
+                    quote! { (#key, #value) },
+                )
+            }
+            CollectionKind::Elements(elem_ty) => {
+                let elem_ty = into_type(elem_ty);
+                let value = into_value(quote! { value });
+                (Generics::default(), quote! { value: #elem_ty }, value)
+            }
+            CollectionKind::Unknown => {
+                let mut generics: Generics = parse_quote! { <VALUE> };
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #ty: ::core::iter::Extend<VALUE> });
+                (generics, quote! { value: VALUE }, quote! { value })
+            }
+        };
+        let vis = &self.setter_vis;
+        pattern.setter_method(vis, method_name, &generics, args, |builder| {
+            quote! {
+                ::core::iter::Extend::extend(
+                    #builder.#name.get_or_insert_with(::core::default::Default::default),
+                    ::core::iter::once(#item),
+                );
+            }
+        })
+    }
+
+    // Combine this field of two builders, `over` taking precedence over `base`:
+    //     builder.host = over.host.or(base.host);
+    // Std collections with an `each` setter are appended to, `base` items first, and nested
+    // builders are merged field by field.
+    pub fn field_merge_form(
+        &self,
+        builder: &proc_macro2::TokenStream,
+        base: proc_macro2::TokenStream,
+        over: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        if self.skipped {
+            return quote! {};
+        }
+        let name = format_ident!("{}", self.field_name);
+        if let Some(sub_builder_type) = &self.sub_builder_type {
+            return quote! {
+                #builder.#name = match (#base, #over) {
+                    (::core::option::Option::Some(base), ::core::option::Option::Some(over)) => {
+                        ::core::option::Option::Some(<#sub_builder_type>::__sub_builder_merge(base, over))
                     }
+                    (base, over) => ::core::option::Option::or(over, base),
+                };
+            };
+        }
+        let appends = self.each_name.is_some()
+            && !matches!(collection_kind(self.field_type), CollectionKind::Unknown);
+        if appends {
+            quote! {
+                #builder.#name = match (#base, #over) {
+                    (::core::option::Option::Some(mut base), ::core::option::Option::Some(over)) => {
+                        ::core::iter::Extend::extend(&mut base, over);
+                        ::core::option::Option::Some(base)
+                    }
+                    (base, over) => ::core::option::Option::or(over, base),
+                };
+            }
+        } else {
+            quote! {
+                #builder.#name = ::core::option::Option::or(#over, #base);
+            }
+        }
+    }
+
+    // Field read by fill_from_env(), parsed with FromStr and left as is when the variable is unset:
+    //     match std::env::var("APP_PORT") { Ok(value) => builder.port = Some(value.parse()?), ... }
+    pub fn field_env_form(
+        &self,
+        builder: &proc_macro2::TokenStream,
+        error_ident: &Ident,
+    ) -> proc_macro2::TokenStream {
+        let env_var = match &self.env_var {
+            Some(env_var) => env_var,
+            None => return quote! {},
+        };
+        let name = format_ident!("{}", self.field_name);
+        let value_ty = if self.is_option_type {
+            extract_generic_type(self.field_type).to_token_stream()
+        } else {
+            self.field_type.to_token_stream()
+        };
+        let env_error = quote! {
+            |error| #error_ident::EnvError(#env_var, ::std::string::ToString::to_string(&error))
+        };
+        quote! {
+            match ::std::env::var(#env_var) {
+                ::core::result::Result::Ok(value) => {
+                    #builder.#name = ::core::option::Option::Some(
+                        <#value_ty as ::core::str::FromStr>::from_str(&value).map_err(#env_error)?,
+                    );
                 }
-            } else {
-                quote! {
-                    // Builder each attribute process skipped: no configured value
+                ::core::result::Result::Err(::std::env::VarError::NotPresent) => {}
+                ::core::result::Result::Err(error) => {
+                    return ::core::result::Result::Err((#env_error)(error));
                 }
             }
+        }
+    }
+
+    pub fn has_env_var(&self) -> bool {
+        self.env_var.is_some()
+    }
+
+    // Flag of the field for from_args(), e.g. `--current-dir` for `current_dir`.
+    pub fn args_flag(&self) -> String {
+        let name = self
+            .field_name
+            .trim_start_matches("r#")
+            .trim_start_matches('_');
+        format!("--{}", name.replace('_', "-"))
+    }
+
+    // Type of the value held, T for Option<T> fields.
+    fn inner_value_type(&self) -> &Type {
+        match extract_generic_type(self.field_type) {
+            Some(inner_ty) if self.is_option_type => inner_ty,
+            _ => self.field_type,
+        }
+    }
+
+    // bool fields are set by their flag alone, `--verbose`, or `--verbose=false`.
+    fn is_args_switch(&self) -> bool {
+        matches!(
+            self.inner_value_type(),
+            Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident("bool")
+        )
+    }
+
+    // Line of the field in the from_args() help, e.g. ("--port <PORT>", "Port to listen on."),
+    // none for fields not set by a flag.
+    pub fn field_args_help(&self) -> std::option::Option<(String, String)> {
+        if self.skipped || self.is_sub_builder() {
+            return None;
+        }
+        let flag = self.args_flag();
+        let placeholder = flag.trim_start_matches('-').to_uppercase();
+        let usage = if self.is_args_switch() {
+            flag
+        } else if let CollectionKind::Map(..) = collection_kind(self.field_type) {
+            format!("{} <KEY=VALUE>", flag)
+        } else {
+            format!("{} <{}>", flag, placeholder)
+        };
+        let note = if self.is_collection() {
+            " (repeatable)"
+        } else if self.is_required() {
+            " (required)"
         } else {
+            ""
+        };
+        Some((usage, format!("{}{}", self.doc, note).trim().to_owned()))
+    }
+
+    // Accessors of the builder field, for checking progress before build():
+    //     fn is_set_port(&self) -> bool
+    //     fn get_port(&self) -> Option<&u16>
+    // and unset_port() following the builder pattern, unless the builder is typestate.
+    pub fn field_introspection_form(
+        &self,
+        pattern: BuilderPattern,
+        typestate: bool,
+    ) -> proc_macro2::TokenStream {
+        if self.skipped {
+            return quote! {};
+        }
+        let name = format_ident!("{}", self.field_name);
+        let suffix = self.field_name.trim_start_matches("r#");
+        let (is_set_name, get_name, unset_name) = (
+            format_ident!("is_set_{}", suffix),
+            format_ident!("get_{}", suffix),
+            format_ident!("unset_{}", suffix),
+        );
+        let ty = self.field_type;
+        let value_ty = if let Some(sub_builder_type) = &self.sub_builder_type {
+            sub_builder_type.to_token_stream()
+        } else if self.is_option_type && extract_generic_type(ty).is_none() {
+            // #[builder(option)] alias, `MaybePort` for Option<u16>.
+            quote! { <#ty as ::core::iter::IntoIterator>::Item }
+        } else {
+            self.inner_value_type().to_token_stream()
+        };
+        let vis = &self.setter_vis;
+        let unset = if typestate {
+            quote! {}
+        } else {
+            pattern.setter_method(
+                vis,
+                &unset_name,
+                &Generics::default(),
+                quote! {},
+                |builder| self.field_unset_form(&builder),
+            )
+        };
+        quote! {
+            #vis fn #is_set_name(&self) -> bool {
+                self.#name.is_some()
+            }
+
+            #vis fn #get_name(&self) -> ::core::option::Option<&#value_ty> {
+                self.#name.as_ref()
+            }
+
+            #unset
+        }
+    }
+
+    // Reset the builder field: `builder.port = None;`
+    pub fn field_unset_form(&self, builder: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.skipped {
+            return quote! {};
+        }
+        let name = format_ident!("{}", self.field_name);
+        quote! {
+            #builder.#name = ::core::option::Option::None;
+        }
+    }
+
+    // Arm of the hidden __is_missing(path) behind missing_fields(), telling whether a path of the
+    // `__FIELD_PATHS` const is unset, as build() would report it:
+    //     if path == "port" { return self.port.is_none(); }
+    // Paths of a nested builder are looked up in it, built empty when unset unless defaulted:
+    //     if let Some(path) = path.strip_prefix("server.") { return ...__is_missing(path); }
+    // A nested type using the struct's generic parameters has no paths in the const, the field
+    // itself is missing when anything in it is.
+    pub fn field_missing_form(&self, path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let name = format_ident!("{}", self.field_name);
+        let field_name = self.display_name();
+        if self.is_required() {
+            return quote! {
+                if #path == #field_name {
+                    return self.#name.is_none();
+                }
+            };
+        }
+        if !self.is_sub_builder() {
+            return quote! {};
+        }
+        let ty = self.field_type;
+        let is_missing = |builder: proc_macro2::TokenStream| {
+            if self.uses_struct_params {
+                quote! { ::core::iter::Iterator::next(&mut #builder.missing_fields()).is_some() }
+            } else {
+                quote! { #builder.__is_missing(#path) }
+            }
+        };
+        let (set, unset) = (
+            is_missing(quote! { builder }),
+            is_missing(quote! { <#ty>::builder() }),
+        );
+        let unset = if self.default_value.is_some() {
+            quote! { false }
+        } else {
+            unset
+        };
+        let value = quote! {
+            match &self.#name {
+                ::core::option::Option::Some(builder) => #set,
+                ::core::option::Option::None => #unset,
+            }
+        };
+        if self.uses_struct_params {
+            return quote! {
+                if #path == #field_name {
+                    return #value;
+                }
+            };
+        }
+        let prefix = format!("{}.", field_name);
+        quote! {
+            if let ::core::option::Option::Some(#path) = #path.strip_prefix(#prefix) {
+                return #value;
+            }
+        }
+    }
+
+    // Entry of the `__FIELD_PATHS` const for the field, a prefix and the paths under it:
+    // ("", "port") for a required field, ("server.", <Server>::__FIELD_PATHS) for a nested builder.
+    pub fn field_paths_part(&self) -> std::option::Option<(String, proc_macro2::TokenStream)> {
+        if self.is_required() || (self.is_sub_builder() && self.uses_struct_params) {
+            let field_name = self.display_name();
+            return Some((String::new(), quote! { #field_name }));
+        }
+        if !self.is_sub_builder() {
+            return None;
+        }
+        let ty = self.field_type;
+        let prefix = format!("{}.", self.display_name());
+        Some((prefix, quote! { <#ty>::__FIELD_PATHS }))
+    }
+
+    // Match arm of from_args() setting the field from its flag, `__inline` being the `=value`
+    // part of the argument, otherwise the value is the next argument:
+    //     "--port" => builder.port = Some(u16::from_str(&value)?),
+    // Collections are extended with every occurrence, maps taking `KEY=VALUE`.
+    pub fn field_args_form(
+        &self,
+        builder: &proc_macro2::TokenStream,
+        error_ident: &Ident,
+    ) -> syn::Result<proc_macro2::TokenStream> {
+        if self.skipped || self.is_sub_builder() {
+            return Ok(quote! {});
+        }
+        let name = format_ident!("{}", self.field_name);
+        let flag = self.args_flag();
+        let parse = |ty: &Type, value: proc_macro2::TokenStream| {
             quote! {
-                // Builder each attribute process skipped: non-Vec type.
+                <#ty as ::core::str::FromStr>::from_str(#value).map_err(|error| {
+                    #error_ident::ArgsError(
+                        ::std::format!("invalid value for `{}`: {}", #flag, error),
+                    )
+                })?
             }
+        };
+        if self.is_args_switch() {
+            let value = parse(self.inner_value_type(), quote! { &value });
+            return Ok(quote! {
+                #flag => {
+                    #builder.#name = ::core::option::Option::Some(match __inline {
+                        ::core::option::Option::Some(value) => #value,
+                        ::core::option::Option::None => true,
+                    });
+                }
+            });
+        }
+        let next_value = quote! {
+            let value = match __inline.or_else(|| ::core::iter::Iterator::next(&mut __args)) {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => {
+                    return ::core::result::Result::Err(#error_ident::ArgsError(
+                        ::std::format!("missing value for `{}`", #flag),
+                    ));
+                }
+            };
+        };
+        if !self.is_collection() {
+            let value = parse(self.inner_value_type(), quote! { &value });
+            return Ok(quote! {
+                #flag => {
+                    #next_value
+                    #builder.#name = ::core::option::Option::Some(#value);
+                }
+            });
         }
+        let item = match collection_kind(self.field_type) {
+            CollectionKind::Map(key_ty, value_ty) => {
+                let (key, value) = (
+                    parse(key_ty, quote! { key }),
+                    parse(value_ty, quote! { value }),
+                );
+                quote! {
+                    match value.split_once('=') {
+                        ::core::option::Option::Some((key, value)) => (#key, #value),
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(#error_ident::ArgsError(
+                                ::std::format!("expected `KEY=VALUE` for `{}`", #flag),
+                            ));
+                        }
+                    }
+                }
+            }
+            CollectionKind::Elements(elem_ty) => parse(elem_ty, quote! { &value }),
+            CollectionKind::Unknown => {
+                return Err(syn::Error::new_spanned(
+                    self.field_type,
+                    "`builder(from_args)` only parses std collections, the item type of this \
+                     one is unknown",
+                ));
+            }
+        };
+        Ok(quote! {
+            #flag => {
+                #next_value
+                ::core::iter::Extend::extend(
+                    #builder.#name.get_or_insert_with(::core::default::Default::default),
+                    ::core::iter::once(#item),
+                );
+            }
+        })
     }
 }
 
-// Initialize helper vector.
-pub fn init_field_macro_helpers(
-    struct_data: &Data,
-) -> std::result::Result<Vec<BuilderMacroFieldHelper>, proc_macro::TokenStream> {
+// Initialize helper vector, reporting the errors of all fields together.
+// - struct_attributes: #[builder(...)] on the struct, providing defaults for every field.
+// - builder_vis: visibility of the builder, inherited by setters without #[builder(vis = "...")].
+// - generics: the struct's generic parameters.
+pub fn init_field_macro_helpers<'a>(
+    fields: &'a syn::Fields,
+    struct_attributes: &HashMap<String, std::option::Option<AttrValue>>,
+    builder_vis: &Visibility,
+    generics: &Generics,
+) -> std::result::Result<Vec<BuilderMacroFieldHelper<'a>>, syn::Error> {
     let mut helpers: Vec<BuilderMacroFieldHelper> = Vec::new();
-    if let syn::Data::Struct(data_struct) = &struct_data {
-        if let syn::Fields::Named(fields) = &data_struct.fields {
-            for field in &fields.named {
-                let field_name = field.ident.to_token_stream().to_string();
-                let parsed_attrs = extract_field_attributes(&field, "builder");
-                if parsed_attrs.is_err() {
-                    let error = parsed_attrs.unwrap_err();
-                    return Err(error.to_compile_error().into());
-                }
-                helpers.push(BuilderMacroFieldHelper {
-                    field_name: field_name,
-                    field_type: &field.ty,
-                    is_option_type: is_type_eq(&field.ty, "Option"),
-                    is_vec_type: is_type_eq(&field.ty, "Vec"),
-                    field_attributes: parsed_attrs.unwrap(),
-                });
+    let mut errors = Vec::new();
+    let params = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(lifetime) => &lifetime.lifetime.ident,
+            GenericParam::Type(ty) => &ty.ident,
+            GenericParam::Const(constant) => &constant.ident,
+        })
+        .collect::<Vec<_>>();
+    for (index, field) in fields.iter().enumerate() {
+        match init_field_macro_helper(index, field, struct_attributes, builder_vis, &params) {
+            Ok(helper) => helpers.push(helper),
+            Err(error) => errors.push(error),
+        }
+    }
+    match combine_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(helpers),
+    }
+}
+
+fn init_field_macro_helper<'a>(
+    index: usize,
+    field: &'a Field,
+    struct_attributes: &HashMap<String, std::option::Option<AttrValue>>,
+    builder_vis: &Visibility,
+    params: &[&Ident],
+) -> std::result::Result<BuilderMacroFieldHelper<'a>, syn::Error> {
+    // Unset fields are taken from `__default`, the struct's own Default value.
+    let struct_default = struct_attributes.contains_key("default");
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(index)),
+    };
+    let span = match &field.ident {
+        Some(ident) => ident.span(),
+        None => syn::spanned::Spanned::span(&field.ty),
+    };
+    let field_attributes = extract_attributes(
+        &field.attrs,
+        "builder",
+        &[
+            "each=",
+            "each.name=",
+            "each.into",
+            "name=",
+            "option",
+            "not_option",
+            "sub_builder",
+            "sub_builder.builder=",
+            "default",
+            "setter.into",
+            "setter.strip_option",
+            "try_setter",
+            "vis=",
+            "skip",
+            "compute=",
+            "field_attr(..)",
+            "env=",
+        ],
+        "expected one of `each`, `name`, `option`, `not_option`, `sub_builder`, `default`, \
+         `setter(into, strip_option)`, `try_setter`, `vis`, `skip`, `compute`, `field_attr(...)`, \
+         `env`",
+    )?;
+    // #[builder(field_attr(...))], put on the builder's field.
+    let builder_field_attrs = parse_token_list::<syn::Meta>(&field_attributes, "field_attr")?;
+    let field_name = match (field_attributes.get("name"), &field.ident) {
+        (Some(name), _) => match name.as_deref().map(syn::parse_str::<Ident>) {
+            Some(Ok(name)) => name.to_string(),
+            _ => {
+                let error =
+                    attribute_error(name, field, "expected `builder(name = \"identifier\")`");
+                return Err(error);
+            }
+        },
+        (None, Some(ident)) => ident.to_string(),
+        (None, None) => format!("_{}", index),
+    };
+    let each_name = match field_attributes
+        .get("each")
+        .or_else(|| field_attributes.get("each.name"))
+    {
+        Some(each_name) => match each_name.as_deref().map(syn::parse_str::<Ident>) {
+            Some(Ok(each_name)) => Some(each_name),
+            _ => {
+                let error = attribute_error(
+                    each_name,
+                    field,
+                    "expected `builder(each = \"identifier\")`",
+                );
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+    let default_value = match field_attributes.get("default") {
+        // #[builder(default = "expr")]
+        Some(Some(expr)) => match syn::parse_str::<Expr>(expr) {
+            Ok(expr) => Some(expr.to_token_stream()),
+            Err(error) => {
+                let error = syn::Error::new(
+                    expr.span,
+                    format!("invalid `builder(default = ...)` expression: {}", error),
+                );
+                return Err(error);
+            }
+        },
+        // #[builder(default)]
+        Some(None) => Some(quote! { ::core::default::Default::default() }),
+        None if struct_default => Some(quote! { __default.#member }),
+        None => None,
+    };
+    let compute_expr = match field_attributes.get("compute") {
+        Some(Some(expr)) => match syn::parse_str::<Expr>(expr) {
+            Ok(expr) => Some(expr),
+            Err(error) => {
+                let error = syn::Error::new(
+                    expr.span,
+                    format!("invalid `builder(compute = ...)` expression: {}", error),
+                );
+                return Err(error);
+            }
+        },
+        _ => None,
+    };
+    // Fields left out of the builder start from their default, unless computed.
+    let skipped =
+        flag_attribute(&field_attributes, "skip").unwrap_or(false) || compute_expr.is_some();
+    let default_value = match default_value {
+        None if skipped && compute_expr.is_none() => {
+            Some(quote! { ::core::default::Default::default() })
+        }
+        default_value => default_value,
+    };
+    // Field level setter options override the struct level ones.
+    let setter_into = flag_attribute(&field_attributes, "setter.into")
+        .or_else(|| flag_attribute(struct_attributes, "setter.into"))
+        .unwrap_or(false);
+    let try_setter = flag_attribute(&field_attributes, "try_setter")
+        .or_else(|| flag_attribute(struct_attributes, "try_setter"))
+        .unwrap_or(false);
+    // #[builder(option)] / #[builder(not_option)] for aliases the macro can't see through.
+    let is_option_type = match (
+        flag_attribute(&field_attributes, "option"),
+        flag_attribute(&field_attributes, "not_option"),
+    ) {
+        (Some(true), Some(true)) => {
+            let error = syn::Error::new_spanned(
+                field,
+                "`builder(option)` and `builder(not_option)` are mutually exclusive",
+            );
+            return Err(error);
+        }
+        (Some(true), _) => true,
+        (_, Some(true)) => false,
+        _ => is_option_type(&field.ty),
+    };
+    // With #[builder(from_args)], a bool switch whose flag isn't given builds as false, while its
+    // builder field stays unset for merge().
+    let is_switch = !is_option_type
+        && matches!(&field.ty, Type::Path(type_path)
+            if type_path.qself.is_none() && type_path.path.is_ident("bool"));
+    let default_value = match default_value {
+        None if is_switch
+            && !skipped
+            && flag_attribute(struct_attributes, "from_args") == Some(true) =>
+        {
+            Some(quote! { false })
+        }
+        default_value => default_value,
+    };
+    // Stripping needs the T of Option<T>, which an alias like `MaybePort` doesn't show.
+    let has_inner_type = extract_generic_type(&field.ty).is_some();
+    let strip_option = match flag_attribute(&field_attributes, "setter.strip_option") {
+        Some(true) if !is_option_type => {
+            let error = syn::Error::new_spanned(
+                field,
+                "`builder(setter(strip_option))` requires an Option field",
+            );
+            return Err(error);
+        }
+        Some(true) if !has_inner_type => {
+            let error = syn::Error::new_spanned(
+                field,
+                "`builder(setter(strip_option))` requires the field type to be written as \
+                 `Option<T>`",
+            );
+            return Err(error);
+        }
+        Some(strip_option) => strip_option,
+        None => {
+            has_inner_type
+                && flag_attribute(struct_attributes, "setter.strip_option").unwrap_or(true)
+        }
+    };
+    let setter_vis = match field_attributes.get("vis") {
+        Some(vis) => parse_visibility(vis, field)?,
+        None => builder_vis.clone(),
+    };
+    let builder_path = match field_attributes.get("sub_builder.builder") {
+        Some(path) => match path.as_deref().map(syn::parse_str::<syn::Path>) {
+            Some(Ok(path)) => Some(path),
+            _ => {
+                let error = attribute_error(
+                    path,
+                    field,
+                    "expected `builder(sub_builder(builder = \"path::to::Builder\"))`",
+                );
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+    let is_sub_builder = field_attributes.contains_key("sub_builder") || builder_path.is_some();
+    let sub_builder_type = if is_sub_builder && !skipped {
+        match sub_builder_type(&field.ty, builder_path) {
+            Some(ty) if !is_option_type => Some(ty),
+            _ => {
+                let error = syn::Error::new_spanned(
+                    &field.ty,
+                    "`builder(sub_builder)` requires a struct type deriving Builder",
+                );
+                return Err(error);
+            }
+        }
+    } else {
+        None
+    };
+    // #[builder(env = "APP_PORT")] parses a single value, which skipped fields, nested builders
+    // and collections don't take.
+    let env_var = match field_attributes.get("env") {
+        Some(Some(env_var)) if skipped || sub_builder_type.is_some() => {
+            let error = syn::Error::new_spanned(
+                field,
+                format!(
+                    "`builder(env = \"{}\")` is not supported on skipped or sub_builder fields",
+                    env_var.value
+                ),
+            );
+            return Err(error);
+        }
+        Some(Some(env_var)) if is_vec_type(&field.ty) || each_name.is_some() => {
+            let error = syn::Error::new_spanned(
+                field,
+                format!(
+                    "`builder(env = \"{}\")` is not supported on collection fields",
+                    env_var.value
+                ),
+            );
+            return Err(error);
+        }
+        Some(Some(_)) if is_option_type && !has_inner_type => {
+            let error = syn::Error::new_spanned(
+                field,
+                "`builder(env = \"...\")` requires the field type to be written as `Option<T>`",
+            );
+            return Err(error);
+        }
+        env_var => env_var.cloned().flatten().map(|env_var| env_var.value),
+    };
+    let doc = doc_comment(&field.attrs);
+    Ok(BuilderMacroFieldHelper {
+        field_name,
+        member,
+        span,
+        field_type: &field.ty,
+        is_option_type,
+        is_vec_type: is_vec_type(&field.ty),
+        field_attributes,
+        default_value,
+        each_name,
+        setter_into,
+        strip_option,
+        try_setter,
+        setter_vis,
+        sub_builder_type,
+        uses_struct_params: type_uses_params(field.ty.to_token_stream(), params),
+        skipped,
+        compute_expr,
+        builder_field_attrs,
+        env_var,
+        doc,
+    })
+}
+
+// Text of the `///` doc comment among the attributes, lines joined with spaces.
+pub fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(line),
+                    ..
+                }) => Some(line.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Comma separated list passed through from #[builder(KEY(...))], empty when absent.
+pub fn parse_token_list<T: syn::parse::Parse>(
+    attrs_map: &HashMap<String, std::option::Option<AttrValue>>,
+    key: &str,
+) -> std::result::Result<Vec<T>, syn::Error> {
+    match attrs_map.get(key) {
+        Some(Some(tokens)) => {
+            let parser = syn::punctuated::Punctuated::<T, Token![,]>::parse_terminated;
+            match syn::parse::Parser::parse_str(parser, tokens) {
+                Ok(list) => Ok(list.into_iter().collect()),
+                Err(error) => Err(syn::Error::new(
+                    tokens.span,
+                    format!("invalid `builder({}(...))`: {}", key, error),
+                )),
+            }
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+// `CirclePoint` gives `circle_point` and `HttpURL` gives `http_url`, used for the builder
+// constructors of enum variants.
+pub fn snake_case(name: &str) -> String {
+    let chars = name.trim_start_matches("r#").chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let after_lower = !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let before_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || (chars[i - 1].is_uppercase() && before_lower) {
+                snake.push('_');
             }
         }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+// Builder of a struct type deriving Builder: `path::Server<T>` gives `path::ServerBuilder<T>`,
+// or the given builder path, which takes the type's generic arguments unless it has its own.
+fn sub_builder_type(
+    ty: &Type,
+    builder_path: std::option::Option<syn::Path>,
+) -> std::option::Option<Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let type_segment = type_path.path.segments.last()?;
+    let path = match builder_path {
+        Some(mut path) => {
+            let last_segment = path.segments.last_mut()?;
+            if last_segment.arguments.is_empty() {
+                last_segment.arguments = type_segment.arguments.clone();
+            }
+            path
+        }
+        None => {
+            let mut path = type_path.path.clone();
+            let last_segment = path.segments.last_mut()?;
+            last_segment.ident = format_ident!("{}Builder", type_segment.ident);
+            path
+        }
+    };
+    Some(Type::Path(syn::TypePath { qself: None, path }))
+}
+
+// Parse #[builder(vis = "...")], an empty string being private.
+pub fn parse_visibility(
+    vis: &std::option::Option<AttrValue>,
+    tokens: impl ToTokens,
+) -> std::result::Result<Visibility, syn::Error> {
+    vis.as_deref()
+        .map(syn::parse_str::<Visibility>)
+        .and_then(|result| result.ok())
+        .ok_or_else(|| {
+            attribute_error(
+                vis,
+                tokens,
+                "expected `builder(vis = \"pub(crate)\")` or similar",
+            )
+        })
+}
+
+// Value of a boolean option: #[builder(ATTR)] or #[builder(ATTR = true)] are true.
+pub fn flag_attribute(
+    attrs_map: &HashMap<String, std::option::Option<AttrValue>>,
+    key: &str,
+) -> std::option::Option<bool> {
+    attrs_map
+        .get(key)
+        .map(|value| value.as_deref() != Some("false"))
+}
+
+// Check whether any of the type parameters appears in the token stream of a type.
+fn type_uses_params(tokens: proc_macro2::TokenStream, type_params: &[&Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => type_params.contains(&&ident),
+        proc_macro2::TokenTree::Group(group) => type_uses_params(group.stream(), type_params),
+        _ => false,
+    })
+}
+
+// Paths under which the macro recognizes Option<T> and Vec<T>, with or without a leading `::`.
+const OPTION_PATHS: &[&str] = &["Option", "std::option::Option", "core::option::Option"];
+const VEC_PATHS: &[&str] = &["Vec", "std::vec::Vec", "alloc::vec::Vec"];
+
+// Path of a generic type as written, e.g. "std::option::Option" for `::std::option::Option<T>`.
+// None for anything else: a reference, a type without generic arguments, `<T as Trait>::Assoc`.
+fn generic_type_path(ty: &Type) -> std::option::Option<String> {
+    let type_path = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path,
+        _ => return None,
+    };
+    let segments = type_path.path.segments.iter().collect::<Vec<_>>();
+    let (last_segment, other_segments) = segments.split_last()?;
+    if !matches!(last_segment.arguments, PathArguments::AngleBracketed(_))
+        || other_segments
+            .iter()
+            .any(|segment| !segment.arguments.is_empty())
+    {
+        return None;
+    }
+    let path = segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+    Some(path.join("::"))
+}
+
+pub fn is_option_type(ty: &Type) -> bool {
+    generic_type_path(ty).is_some_and(|path| OPTION_PATHS.contains(&path.as_str()))
+}
+
+pub fn is_vec_type(ty: &Type) -> bool {
+    generic_type_path(ty).is_some_and(|path| VEC_PATHS.contains(&path.as_str()))
+}
+
+// `HashSet<T>` or a path to it in std or alloc, e.g. `std::collections::hash_set::HashSet<T>`.
+fn is_std_collection(ty: &Type, name: &str) -> bool {
+    generic_type_path(ty).is_some_and(|path| {
+        path == name
+            || ((path.starts_with("std::collections::")
+                || path.starts_with("alloc::collections::"))
+                && path.ends_with(&format!("::{}", name)))
+    })
+}
+
+// Element type of a collection filled by an `each` setter.
+enum CollectionKind<'a> {
+    // HashMap<K, V>, BTreeMap<K, V>: extended with (K, V) pairs.
+    Map(&'a Type, &'a Type),
+    // Vec<T>, HashSet<T>, ...: extended with T.
+    Elements(&'a Type),
+    // Any other Extend + Default type.
+    Unknown,
+}
+
+fn collection_kind(ty: &Type) -> CollectionKind<'_> {
+    let args = generic_type_arguments(ty);
+    let is_any_of = |names: &[&str]| names.iter().any(|name| is_std_collection(ty, name));
+    match args.as_slice() {
+        [key, value, ..] if is_any_of(&["HashMap", "BTreeMap"]) => CollectionKind::Map(key, value),
+        [elem, ..]
+            if is_vec_type(ty)
+                || is_any_of(&[
+                    "VecDeque",
+                    "LinkedList",
+                    "HashSet",
+                    "BTreeSet",
+                    "BinaryHeap",
+                ]) =>
+        {
+            CollectionKind::Elements(elem)
+        }
+        _ => CollectionKind::Unknown,
     }
-    Ok(helpers)
 }
 
-// Check whether type (ty) is the specified type (tystr).
-pub fn is_type_eq(ty: &Type, tystr: &str) -> bool {
+// Type arguments of the last path segment, e.g. [K, V] for std::collections::HashMap<K, V>.
+fn generic_type_arguments(ty: &Type) -> Vec<&Type> {
     if let Type::Path(type_path) = ty {
-        if let std::option::Option::Some(last_segment) = type_path.path.segments.last() {
-            return last_segment.ident == tystr;
+        if let Some(last_segment) = type_path.path.segments.last() {
+            if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
+                return args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect();
+            }
         }
     }
-    false
+    Vec::new()
 }
 
-// Extract T from Option<T>, Vec<T>, etc.
+// Extract T from Option<T>, Vec<T>, etc., one level only: Option<Vec<String>> gives Vec<String>.
 fn extract_generic_type(ty: &Type) -> std::option::Option<&Type> {
-    match ty {
-        Type::Path(ty_path) => {
-            for segment in &ty_path.path.segments {
-                if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                    if let std::option::Option::Some(GenericArgument::Type(arg)) = args.args.first()
-                    {
-                        return extract_generic_type(arg).or(std::option::Option::Some(arg));
-                    }
-                }
+    generic_type_arguments(ty).first().copied()
+}
+
+// How setters and build() take the builder, from #[builder(pattern = "...")].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BuilderPattern {
+    // fn field(&mut self, ...) -> &mut Self, fn build(&self), the default.
+    Mutable,
+    // fn field(self, ...) -> Self, fn build(self), fields need not be Clone.
+    Owned,
+    // fn field(&self, ...) -> Self returning an updated clone, fn build(&self).
+    Immutable,
+}
+
+impl BuilderPattern {
+    pub fn from_name(name: &str) -> std::option::Option<Self> {
+        match name {
+            "mutable" => Some(BuilderPattern::Mutable),
+            "owned" => Some(BuilderPattern::Owned),
+            "immutable" => Some(BuilderPattern::Immutable),
+            _ => None,
+        }
+    }
+
+    // Receiver of build(): only the owned pattern moves the fields out of the builder.
+    pub fn build_receiver(self) -> proc_macro2::TokenStream {
+        match self {
+            BuilderPattern::Owned => quote! { self },
+            BuilderPattern::Mutable | BuilderPattern::Immutable => quote! { &self },
+        }
+    }
+
+    // Chained setter method, `update` generates the statements applied to the given builder.
+    pub fn setter_method(
+        self,
+        vis: &Visibility,
+        method_name: &Ident,
+        generics: &Generics,
+        args: proc_macro2::TokenStream,
+        update: impl Fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let (receiver, return_ty, builder, prologue, generics) = self.setter_signature(generics);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let update = update(builder.clone());
+        quote! {
+            #vis fn #method_name #impl_generics(#receiver, #args) -> #return_ty
+            #where_clause
+            {
+                #prologue
+                #update
+                #builder
+            }
+        }
+    }
+
+    // Same as setter_method, returning `Result<_, error_ty>` so `update` may use `?`.
+    pub fn try_setter_method(
+        self,
+        vis: &Visibility,
+        method_name: &Ident,
+        generics: &Generics,
+        args: proc_macro2::TokenStream,
+        error_ty: proc_macro2::TokenStream,
+        update: impl Fn(proc_macro2::TokenStream) -> proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let (receiver, return_ty, builder, prologue, generics) = self.setter_signature(generics);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let update = update(builder.clone());
+        quote! {
+            #vis fn #method_name #impl_generics(#receiver, #args)
+                -> ::core::result::Result<#return_ty, #error_ty>
+            #where_clause
+            {
+                #prologue
+                #update
+                ::core::result::Result::Ok(#builder)
+            }
+        }
+    }
+
+    // Receiver, return type, builder being updated, statements preparing it and the
+    // method generics; the immutable pattern updates a clone of the builder.
+    fn setter_signature(
+        self,
+        generics: &Generics,
+    ) -> (
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        Generics,
+    ) {
+        let mut generics = generics.clone();
+        match self {
+            BuilderPattern::Mutable => (
+                quote! { &mut self },
+                quote! { &mut Self },
+                quote! { self },
+                quote! {},
+                generics,
+            ),
+            BuilderPattern::Owned => (
+                quote! { mut self },
+                quote! { Self },
+                quote! { self },
+                quote! {},
+                generics,
+            ),
+            BuilderPattern::Immutable => {
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { Self: ::core::clone::Clone });
+                (
+                    quote! { &self },
+                    quote! { Self },
+                    quote! { __builder },
+                    quote! { let mut __builder = ::core::clone::Clone::clone(self); },
+                    generics,
+                )
             }
         }
-        _ => {}
     }
-    // Not a generic type
-    None
+}
+
+// Generic arguments matching the parameters, e.g. `'a, T, N` for `<'a, T: Clone, const N: usize>`.
+pub fn generic_arguments(generics: &Generics) -> Vec<proc_macro2::TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(lifetime) => lifetime.lifetime.to_token_stream(),
+            GenericParam::Type(ty) => ty.ident.to_token_stream(),
+            GenericParam::Const(constant) => constant.ident.to_token_stream(),
+        })
+        .collect()
+}
+
+/// Value of an attribute key, keeping the span of the literal for errors pointing at it.
+#[derive(Clone)]
+pub struct AttrValue {
+    pub value: String,
+    pub span: proc_macro2::Span,
+}
+
+impl std::ops::Deref for AttrValue {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+// Error pointing at the value of an attribute, or at `tokens` for a key written without one.
+pub fn attribute_error(
+    value: &std::option::Option<AttrValue>,
+    tokens: impl ToTokens,
+    message: impl std::fmt::Display,
+) -> syn::Error {
+    match value {
+        Some(value) => syn::Error::new(value.span, message),
+        None => syn::Error::new_spanned(tokens, message),
+    }
 }
 
 /// Return ATTR/VALUE HashMap
 /// - #[Builder(ATTR = "VALUE")], save as "ATTR", Some("VALUE")
 /// - #[Builder(ATTR)], save as "ATTR", None
-fn extract_field_attributes(
-    field: &Field,
+/// - #[Builder(ATTR(NESTED = "VALUE"))], save as "ATTR.NESTED", Some("VALUE")
+/// - #[Builder(ATTR(TOKENS))] for an accepted key "ATTR(..)", save as "ATTR", Some("TOKENS"),
+///   repeated lists being joined with commas
+///
+/// An accepted key ending with `=`, like "each=", requires a value. Every unknown key and
+/// malformed value is reported, combined into one error.
+pub fn extract_attributes(
+    attrs: &[Attribute],
     target_ident: &str,
-) -> std::result::Result<HashMap<String, std::option::Option<String>>, syn::Error> {
+    accepted_keys: &[&str],
+    expected_message: &str,
+) -> std::result::Result<HashMap<String, std::option::Option<AttrValue>>, syn::Error> {
     let mut attrs_map = HashMap::new();
-    let mut compile_error = None;
-    // Get attributes from the field.
-    for attr in &field.attrs {
+    let mut errors = Vec::new();
+    // Get attributes from the field or struct.
+    for attr in attrs {
         if attr.path().is_ident(target_ident) {
-            let _ = attr.parse_nested_meta(|nested_meta| {
-                // ATTR
-                let key = nested_meta.path.get_ident().to_token_stream().to_string();
-                if key == "each" {
-                    // VALUE
-                    let value = nested_meta
-                        .value()
-                        .map(|val| val.parse::<LitStr>()) // Turn value into std::result::Result<LitStr, _>
-                        .and_then(|result| result.map(|lit_str| lit_str.value())) // Parse LitStr into String
-                        .ok(); // Turn std::result::Result into Option
-                    attrs_map.insert(key, value);
-                } else {
-                    compile_error = Some(syn::Error::new_spanned(
-                        attr.meta.to_token_stream(),
-                        "expected `builder(each = \"...\")`",
-                    ));
-                }
-
-                Ok(())
+            let result = attr.parse_nested_meta(|nested_meta| {
+                extract_nested_attribute(
+                    nested_meta,
+                    "",
+                    accepted_keys,
+                    expected_message,
+                    &mut attrs_map,
+                    &mut errors,
+                )
             });
+            if let Err(error) = result {
+                errors.push(error);
+            }
         }
     }
-    if compile_error.is_some() {
-        Err(compile_error.unwrap())
+    match combine_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(attrs_map),
+    }
+}
+
+// Save one ATTR of the attribute list, recursing into ATTR(...) lists. Unknown keys and missing
+// values are collected into `errors` so the rest of the list is still checked.
+fn extract_nested_attribute(
+    nested_meta: ParseNestedMeta,
+    prefix: &str,
+    accepted_keys: &[&str],
+    expected_message: &str,
+    attrs_map: &mut HashMap<String, std::option::Option<AttrValue>>,
+    errors: &mut Vec<syn::Error>,
+) -> syn::Result<()> {
+    // ATTR
+    let name = nested_meta.path.get_ident().to_token_stream().to_string();
+    let key = format!("{}{}", prefix, name);
+    let nested_prefix = format!("{}.", key);
+    let requires_value = accepted_keys.contains(&format!("{}=", key).as_str());
+    if accepted_keys.contains(&format!("{}(..)", key).as_str()) {
+        // TOKENS, passed through as written.
+        if !nested_meta.input.peek(syn::token::Paren) {
+            errors.push(nested_meta.error(format!("expected `{}(...)`", name)));
+            return skip_nested_value(&nested_meta);
+        }
+        let content;
+        let paren = parenthesized!(content in nested_meta.input);
+        let tokens = content.parse::<proc_macro2::TokenStream>()?.to_string();
+        let value = match attrs_map.remove(&key) {
+            Some(Some(previous)) => AttrValue {
+                value: format!("{}, {}", previous.value, tokens),
+                span: previous.span,
+            },
+            _ => AttrValue {
+                value: tokens,
+                span: paren.span.join(),
+            },
+        };
+        attrs_map.insert(key, Some(value));
+        Ok(())
+    } else if nested_meta.input.peek(syn::token::Paren)
+        && accepted_keys
+            .iter()
+            .any(|accepted| accepted.starts_with(&nested_prefix))
+    {
+        nested_meta.parse_nested_meta(|inner_meta| {
+            extract_nested_attribute(
+                inner_meta,
+                &nested_prefix,
+                accepted_keys,
+                expected_message,
+                attrs_map,
+                errors,
+            )
+        })
+    } else if requires_value || accepted_keys.contains(&key.as_str()) {
+        // VALUE
+        let value = if nested_meta.input.peek(Token![=]) {
+            let lit = nested_meta.value()?.parse::<Lit>()?;
+            let value = match &lit {
+                Lit::Str(lit_str) => lit_str.value(),
+                Lit::Bool(lit_bool) => lit_bool.value.to_string(),
+                lit => lit.to_token_stream().to_string(),
+            };
+            Some(AttrValue {
+                value,
+                span: lit.span(),
+            })
+        } else if requires_value {
+            errors.push(nested_meta.error(format!("expected `{} = \"...\"`", name)));
+            skip_nested_value(&nested_meta)?;
+            return Ok(());
+        } else {
+            None
+        };
+        attrs_map.insert(key, value);
+        Ok(())
     } else {
-        Ok(attrs_map)
+        let message = match suggest_key(&key, prefix, accepted_keys) {
+            Some(suggestion) => format!(
+                "unknown attribute `{}`, did you mean `{}`?",
+                name, suggestion
+            ),
+            None => expected_message.to_owned(),
+        };
+        errors.push(nested_meta.error(message));
+        skip_nested_value(&nested_meta)
     }
 }
+
+// Consume the `= value` or `(...)` following an attribute key that is reported as an error.
+fn skip_nested_value(nested_meta: &ParseNestedMeta) -> syn::Result<()> {
+    if nested_meta.input.peek(Token![=]) {
+        nested_meta.value()?.parse::<Expr>()?;
+    } else if nested_meta.input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in nested_meta.input);
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(())
+}
+
+// Closest accepted key at the same nesting level as an unknown one, for typos like `eac`.
+fn suggest_key(key: &str, prefix: &str, accepted_keys: &[&str]) -> std::option::Option<String> {
+    let name = &key[prefix.len()..];
+    accepted_keys
+        .iter()
+        .filter_map(|accepted| accepted.strip_prefix(prefix))
+        .map(|accepted| {
+            accepted
+                .split(['.', '=', '('])
+                .next()
+                .unwrap_or(accepted)
+                .to_owned()
+        })
+        .map(|candidate| (edit_distance(name, &candidate), candidate))
+        .filter(|(distance, _)| {
+            *distance > 0 && *distance < name.len() && *distance <= std::cmp::max(2, name.len() / 3)
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Edit distance between two attribute names, a swap of adjacent letters counting as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+// All errors as one, reported together.
+pub fn combine_errors(errors: Vec<syn::Error>) -> std::option::Option<syn::Error> {
+    errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    })
+}
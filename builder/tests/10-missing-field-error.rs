@@ -0,0 +1,56 @@
+// Calling build() with a required field unset must not panic. The derive
+// generates a `{Struct}BuilderError` enum implementing std::error::Error and
+// Display, and build() returns its MissingField variant naming the field,
+// without the `r#` of raw identifiers.
+//
+//     #[derive(Debug)]
+//     pub enum CommandBuilderError {
+//         MissingField(&'static str),
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Token {
+    r#type: String,
+}
+
+fn assert_error<E: std::error::Error>(_: &E) {}
+
+fn main() {
+    let error = Command::builder().build().err().unwrap();
+    assert_error(&error);
+    assert!(matches!(
+        error,
+        CommandBuilderError::MissingField("executable")
+    ));
+    assert_eq!(error.to_string(), "missing field `executable`");
+
+    // Still converts into a boxed error with `?`.
+    let boxed = || -> Result<Command, Box<dyn std::error::Error>> {
+        let command = Command::builder().build()?;
+        Ok(command)
+    };
+    assert!(boxed().is_err());
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert!(command.args.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let error = Token::builder().build().err().unwrap();
+    assert!(matches!(error, TokenBuilderError::MissingField("type")));
+    assert_eq!(error.to_string(), "missing field `type`");
+    let token = Token::builder().r#type("ident".to_owned()).build().unwrap();
+    assert_eq!(token.r#type, "ident");
+}
@@ -0,0 +1,67 @@
+// Generic structs get a builder carrying the same lifetime, type and const
+// parameters, trait bounds and where clause as the original struct.
+//
+//     pub struct ConnBuilder<'a, T: Transport, const N: usize>
+//     where
+//         T: Clone,
+//     {
+//         ...
+//     }
+//
+// The builder, its setters, the builder() constructor and build() are all
+// generic over those parameters, so the derive works for config types that are
+// generic over a transport or hold a borrowed `&'a str`.
+
+use derive_builder::Builder;
+
+pub trait Transport {
+    fn scheme(&self) -> &'static str;
+}
+
+#[derive(Clone)]
+pub struct Tcp;
+
+impl Transport for Tcp {
+    fn scheme(&self) -> &'static str {
+        "tcp"
+    }
+}
+
+#[derive(Builder)]
+pub struct Conn<'a, T: Transport, const N: usize>
+where
+    T: Clone,
+{
+    host: &'a str,
+    transport: T,
+    retries: [u8; N],
+    fallback: Option<T>,
+}
+
+#[derive(Builder)]
+pub struct Labeled<T> {
+    label: String,
+    value: T,
+}
+
+fn main() {
+    let host = String::from("localhost");
+    let conn: Conn<'_, Tcp, 2> = Conn::builder()
+        .host(&host)
+        .transport(Tcp)
+        .retries([1, 2])
+        .build()
+        .unwrap();
+
+    assert_eq!(conn.host, "localhost");
+    assert_eq!(conn.transport.scheme(), "tcp");
+    assert_eq!(conn.retries, [1, 2]);
+    assert!(conn.fallback.is_none());
+
+    let labeled = Labeled::builder()
+        .label("answer".to_owned())
+        .value(42)
+        .build()
+        .unwrap();
+    assert_eq!(labeled.value, 42);
+}
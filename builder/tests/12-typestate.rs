@@ -0,0 +1,68 @@
+// With #[builder(typestate)] on the struct, the builder tracks every required
+// field (anything that is neither Option nor Vec) in its type. Setters consume
+// the builder and return it with the field marked as set, and build() only
+// exists once all required fields have been set.
+//
+//     let command = Command::builder()
+//         .executable("cargo".to_owned())
+//         .current_dir("..".to_owned())
+//         .build()
+//         .unwrap();
+//
+// Setting a required field twice is a type error as well, and build() takes
+// the builder by value so none of the fields need to be Clone.
+
+use derive_builder::Builder;
+
+pub struct Handle(u32);
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    handle: Handle,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Scoped<'a, T> {
+    name: &'a str,
+    value: T,
+}
+
+// Defaults of the struct's parameters are dropped on the builder, whose state
+// parameters come last.
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job<T = u8, const N: usize = 4> {
+    id: T,
+    slots: [u8; N],
+}
+
+fn main() {
+    // Required fields can be set in any order, optional ones at any point.
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .handle(Handle(3))
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .arg("--release".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.handle.0, 3);
+
+    let scoped = Scoped::builder().value(7u8).name("seven").build().unwrap();
+    assert_eq!(scoped.name, "seven");
+    assert_eq!(scoped.value, 7);
+
+    let job: Job = Job::builder().id(1).slots([0; 4]).build().unwrap();
+    assert_eq!(job.id, 1);
+    assert_eq!(job.slots.len(), 4);
+}
@@ -0,0 +1,18 @@
+// In typestate mode a missing required field is a compile error instead of a
+// runtime MissingField error: build() is not defined until every required
+// field is set.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: String,
+}
+
+fn main() {
+    let _ = Command::builder()
+        .executable("cargo".to_owned())
+        .build();
+}
@@ -0,0 +1,84 @@
+// Fields can be left unset without making them Option:
+//
+//   - #[builder(default)] falls back to Default::default(),
+//   - #[builder(default = "expr")] falls back to an arbitrary expression,
+//   - #[builder(default)] on the struct seeds every unset field from the
+//     struct's own Default impl.
+//
+// Defaulted fields are never reported as missing, and they are not required
+// in typestate mode either.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    verbose: bool,
+    #[builder(default = "String::from(\"..\")")]
+    current_dir: String,
+    #[builder(default = "Some(30)")]
+    timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default = "4")]
+    workers: usize,
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+            workers: 1,
+            tags: vec!["default".to_owned()],
+            name: Some("main".to_owned()),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job<T> {
+    name: String,
+    #[builder(default)]
+    payload: T,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(!command.verbose);
+    assert_eq!(command.current_dir, "..");
+    assert_eq!(command.timeout, Some(30));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .verbose(true)
+        .current_dir("/tmp".to_owned())
+        .timeout(5)
+        .build()
+        .unwrap();
+    assert!(command.verbose);
+    assert_eq!(command.current_dir, "/tmp");
+    assert_eq!(command.timeout, Some(5));
+
+    let server = Server::builder().port(9000).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 9000);
+    assert_eq!(server.workers, 4);
+    assert_eq!(server.tags, vec!["default"]);
+    assert_eq!(server.name.as_deref(), Some("main"));
+
+    let job: Job<Vec<u8>> = Job::builder().name("empty".to_owned()).build().unwrap();
+    assert!(job.payload.is_empty());
+}
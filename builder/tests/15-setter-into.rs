@@ -0,0 +1,62 @@
+// #[builder(setter(into))] makes a setter generic over `impl Into<T>`, so
+// callers can write `.executable("cargo")` instead of converting first. It can
+// be given on a field or on the struct, where it applies to every field.
+//
+// Setters of Option<T> fields take T by default, which is the same as writing
+// #[builder(setter(strip_option))]. Opt out with
+// #[builder(setter(strip_option = false))] to pass the Option<T> directly.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    args: Vec<String>,
+    #[builder(setter(into, strip_option))]
+    current_dir: Option<PathBuf>,
+    #[builder(setter(strip_option = false))]
+    timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Server {
+    host: String,
+    port: u32,
+    #[builder(setter(strip_option = false))]
+    name: Option<String>,
+}
+
+fn main() {
+    let timeout = None;
+    let command = Command::builder()
+        .executable("cargo")
+        .args(vec!["build".to_owned()])
+        .current_dir("/tmp")
+        .timeout(timeout)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir, Some(PathBuf::from("/tmp")));
+    assert_eq!(command.timeout, None);
+
+    let command = Command::builder()
+        .executable(String::from("rustc"))
+        .timeout(Some(10))
+        .build()
+        .unwrap();
+    assert_eq!(command.timeout, Some(10));
+
+    // With both into and no strip_option, `T` and `Option<T>` are accepted.
+    let server = Server::builder()
+        .host("localhost")
+        .port(8080u16)
+        .name("main".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.name.as_deref(), Some("main"));
+}
@@ -0,0 +1,67 @@
+// #[builder(pattern = "...")] selects how setters and build() take the
+// builder:
+//
+//   - "mutable", the default: setters take `&mut self` and return `&mut Self`,
+//     build(&self) clones every field.
+//   - "owned": setters consume and return `Self`, build(self) moves the fields
+//     out so they need not implement Clone.
+//   - "immutable": setters take `&self` and return an updated copy of the
+//     builder, build(&self) clones every field.
+
+use derive_builder::Builder;
+use std::sync::mpsc::{channel, Sender};
+
+// Neither Clone nor Copy.
+pub struct Handle(i32);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    name: String,
+    handle: Handle,
+    sender: Sender<String>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    parent: Option<Handle>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Server {
+    port: u16,
+}
+
+fn main() {
+    let (sender, receiver) = channel();
+    let worker = Worker::builder()
+        .name("worker".to_owned())
+        .handle(Handle(3))
+        .sender(sender)
+        .tag("io".to_owned())
+        .build()
+        .unwrap();
+    worker.sender.send(worker.name.clone()).unwrap();
+    assert_eq!(receiver.recv().unwrap(), "worker");
+    assert_eq!(worker.handle.0, 3);
+    assert_eq!(worker.tags, vec!["io"]);
+    assert!(worker.parent.is_none());
+
+    let base = Command::builder().executable("cargo".to_owned());
+    let build = base.arg("build".to_owned());
+    let test = base.arg("test".to_owned()).arg("--release".to_owned());
+    assert!(base.build().unwrap().args.is_empty());
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert_eq!(test.build().unwrap().args, vec!["test", "--release"]);
+
+    let mut builder = Server::builder();
+    builder.port(80);
+    assert_eq!(builder.build().unwrap().port, 80);
+}
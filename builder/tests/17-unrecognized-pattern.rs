@@ -0,0 +1,11 @@
+// An unknown builder pattern is reported as a compile error.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "borrowed")]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
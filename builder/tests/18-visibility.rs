@@ -0,0 +1,70 @@
+// The builder struct, its error type, builder(), the setters and build() all
+// inherit the visibility of the deriving struct, so a builder for a
+// `pub struct` is usable from other modules and crates.
+//
+// #[builder(vis = "...")] overrides it: on the struct for the whole builder,
+// on a field for that field's setter only. An empty string makes an item
+// private to the defining module.
+
+use derive_builder::Builder;
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        pub port: u16,
+        #[builder(vis = "")]
+        pub secret: Option<String>,
+    }
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Client {
+        pub endpoint: String,
+        #[builder(vis = "pub")]
+        pub retries: Option<u8>,
+    }
+
+    pub fn server_with_secret() -> Server {
+        Server::builder()
+            .host("localhost".to_owned())
+            .port(443)
+            .secret("hunter2".to_owned())
+            .build()
+            .unwrap()
+    }
+}
+
+mod app {
+    use crate::config::{Client, ClientBuilder, ClientBuilderError};
+
+    pub fn client() -> Result<Client, ClientBuilderError> {
+        let mut builder: ClientBuilder = Client::builder();
+        builder.endpoint("https://example.com".to_owned()).retries(3);
+        builder.build()
+    }
+}
+
+#[derive(Builder)]
+struct Private {
+    value: u8,
+}
+
+fn main() {
+    let server = config::Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8080);
+    assert!(server.secret.is_none());
+    assert_eq!(config::server_with_secret().secret.as_deref(), Some("hunter2"));
+
+    let client = app::client().unwrap();
+    assert_eq!(client.endpoint, "https://example.com");
+    assert_eq!(client.retries, Some(3));
+
+    assert_eq!(Private::builder().value(1).build().unwrap().value, 1);
+}
@@ -0,0 +1,20 @@
+// A setter made private with #[builder(vis = "")] cannot be called from
+// outside the module defining the struct.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        #[builder(vis = "")]
+        pub secret: Option<String>,
+    }
+}
+
+fn main() {
+    let _ = config::Server::builder()
+        .host("localhost".to_owned())
+        .secret("hunter2".to_owned())
+        .build();
+}
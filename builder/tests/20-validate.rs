@@ -0,0 +1,44 @@
+// #[builder(validate = "path::to::fn")] runs a check on the assembled value
+// inside build(), so invariants spanning several fields no longer need a
+// hand-written constructor.
+//
+// The function takes the candidate value by reference and returns
+// Result<(), E> for any E implementing Display. Its error is surfaced as the
+// ValidationError variant of the builder's error type.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(validate = "checks::range")]
+pub struct Range {
+    min: u32,
+    max: u32,
+}
+
+mod checks {
+    pub fn range(range: &super::Range) -> Result<(), String> {
+        if range.min <= range.max {
+            Ok(())
+        } else {
+            Err(format!("min {} is greater than max {}", range.min, range.max))
+        }
+    }
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(10).build().unwrap();
+    assert_eq!((range.min, range.max), (1, 10));
+
+    let error = Range::builder().min(10).max(1).build().unwrap_err();
+    match &error {
+        RangeBuilderError::ValidationError(message) => {
+            assert_eq!(message, "min 10 is greater than max 1");
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+    assert_eq!(error.to_string(), "min 10 is greater than max 1");
+
+    // Missing fields are still reported before validation runs.
+    let error = Range::builder().min(10).build().unwrap_err();
+    assert!(matches!(error, RangeBuilderError::MissingField("max")));
+}
@@ -0,0 +1,85 @@
+// #[builder(each = "...")] works for any collection implementing Extend and
+// Default, not only Vec:
+//
+//   - HashMap and BTreeMap get a one-at-a-time setter taking a key and a value,
+//   - HashSet, BTreeSet, VecDeque and the other std collections take one
+//     element,
+//   - any other type takes whatever value it can be extended with.
+//
+// The longer form #[builder(each(name = "...", into))] also makes the
+// one-at-a-time setter accept `impl Into<T>` for the element, key and value.
+//
+// Collections with an `each` setter start out empty and are never missing.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    target: String,
+}
+
+#[derive(Clone, Default)]
+pub struct Counter {
+    total: usize,
+}
+
+impl Extend<usize> for Counter {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        self.total += iter.into_iter().sum::<usize>();
+    }
+}
+
+#[derive(Builder)]
+pub struct Router {
+    #[builder(each = "route")]
+    routes: BTreeMap<String, Route>,
+    #[builder(each(name = "header", into))]
+    headers: std::collections::HashMap<String, String>,
+    #[builder(each = "method")]
+    methods: HashSet<&'static str>,
+    #[builder(each(name = "middleware", into))]
+    middlewares: VecDeque<String>,
+    #[builder(each = "count")]
+    requests: Counter,
+    #[builder(each = "tags")]
+    tags: Vec<String>,
+}
+
+fn main() {
+    let router = Router::builder()
+        .route(
+            "/".to_owned(),
+            Route {
+                target: "index".to_owned(),
+            },
+        )
+        .route(
+            "/about".to_owned(),
+            Route {
+                target: "about".to_owned(),
+            },
+        )
+        .header("Accept", "text/html")
+        .method("GET")
+        .method("GET")
+        .middleware("log")
+        .count(2)
+        .count(3)
+        .tags("web".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(router.routes.len(), 2);
+    assert_eq!(router.routes["/about"].target, "about");
+    assert_eq!(router.headers["Accept"], "text/html");
+    assert_eq!(router.methods.len(), 1);
+    assert_eq!(router.middlewares, vec!["log"]);
+    assert_eq!(router.requests.total, 5);
+    assert_eq!(router.tags, vec!["web"]);
+
+    let empty = Router::builder().build().unwrap();
+    assert!(empty.routes.is_empty());
+    assert!(empty.headers.is_empty());
+    assert_eq!(empty.requests.total, 0);
+}
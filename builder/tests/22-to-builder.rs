@@ -0,0 +1,61 @@
+// An existing value can be turned back into a builder with every field
+// already set, modified and rebuilt:
+//
+//   - `impl From<Command> for CommandBuilder` consumes the value,
+//   - `Command::to_builder(&self)` clones it first, and is only callable when
+//     the struct implements Clone.
+//
+// In typestate mode the builder obtained this way has every required field
+// marked as set, so build() can be called right away.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+// Not Clone: only the From conversion is available.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Handle {
+    id: u32,
+    label: Option<String>,
+}
+
+#[derive(Builder, Clone)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    priority: u8,
+}
+
+fn main() {
+    let base = Config::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .tag("base".to_owned())
+        .build()
+        .unwrap();
+
+    let modified = base.to_builder().port(8080).tag("dev".to_owned()).build().unwrap();
+    assert_eq!(modified.host, "localhost");
+    assert_eq!(modified.port, 8080);
+    assert_eq!(modified.tags, vec!["base", "dev"]);
+    assert!(modified.name.is_none());
+    assert_eq!(base.port, 80);
+
+    let handle = Handle::builder().id(1).label("one".to_owned()).build().unwrap();
+    let handle = HandleBuilder::from(handle).id(2).build().unwrap();
+    assert_eq!(handle.id, 2);
+    assert_eq!(handle.label.as_deref(), Some("one"));
+
+    let job = Job::builder().name("backup".to_owned()).priority(1).build().unwrap();
+    let copy = job.to_builder().build().unwrap();
+    assert_eq!(copy.name, "backup");
+    assert_eq!(copy.priority, 1);
+}
@@ -0,0 +1,192 @@
+// #[builder(sub_builder)] on a field whose type derives Builder exposes the
+// nested builder instead of a plain setter:
+//
+//     fn server(&mut self) -> &mut ServerBuilder
+//
+// Owned and immutable builders, which hand out no references, take the
+// nested builder instead, following their pattern like the setters do:
+//
+//     fn server(self, server: ServerBuilder) -> Self
+//
+// The nested builder is looked up as `ServerBuilder` at the path of the field
+// type, so `config::Server` nests `config::ServerBuilder`. Give its path with
+// #[builder(sub_builder(builder = "config::ServerBuilder"))] when it isn't
+// in scope under that name, e.g. for a type brought in with `use`.
+//
+// build() then builds the nested value too, whatever the nested builder's
+// build method. Its errors are wrapped in the parent's SubBuilderError
+// variant naming the field, and remain available through Error::source().
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder, Clone, Debug)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder, Clone, Debug)]
+pub struct Database {
+    url: String,
+    #[builder(default = "8")]
+    pool_size: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct AppConfig {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(sub_builder)]
+    database: Database,
+}
+
+#[derive(Builder, Debug)]
+pub struct Deployment {
+    #[builder(sub_builder)]
+    app: AppConfig,
+}
+
+mod cache {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    #[builder(name = "CacheOptions", build_fn(name = "finish"), pattern = "owned")]
+    pub struct Cache {
+        pub size: usize,
+    }
+}
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    pub struct Limits {
+        pub max: u32,
+    }
+
+    #[derive(Builder, Debug)]
+    pub struct Tagged<T: Clone> {
+        pub tag: T,
+    }
+}
+
+use config::{Limits, Tagged};
+
+#[derive(Builder, Debug)]
+pub struct Quota {
+    #[builder(sub_builder(builder = "config::LimitsBuilder"))]
+    limits: Limits,
+    #[builder(sub_builder(builder = "config::TaggedBuilder"))]
+    label: Tagged<&'static str>,
+}
+
+// The derive adds no items next to the struct, so similar names don't clash.
+#[derive(Builder, Debug)]
+pub struct HttpServer {
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct HTTPServer {
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+pub struct Wrapper<T: Clone> {
+    #[builder(sub_builder)]
+    inner: config::Tagged<T>,
+}
+
+#[derive(Builder, Clone, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Retry {
+    attempts: u8,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    #[builder(sub_builder(builder = "cache::CacheOptions"))]
+    cache: cache::Cache,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Job {
+    #[builder(sub_builder)]
+    retry: Retry,
+}
+
+fn main() {
+    let mut builder = AppConfig::builder();
+    builder.name("app".to_owned());
+    builder.server().host("localhost".to_owned()).port(8080);
+    builder.database().url("postgres://db".to_owned());
+    let config = builder.build().unwrap();
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.database.pool_size, 8);
+
+    // A nested builder is created on first access and can be updated later.
+    let mut builder = AppConfig::builder();
+    builder.name("app".to_owned());
+    builder.database().url("postgres://db".to_owned());
+    builder.server().host("localhost".to_owned());
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "server: missing field `port`");
+    assert_eq!(error.source().unwrap().to_string(), "missing field `port`");
+
+    // Untouched nested builders are built empty.
+    let mut builder = Deployment::builder();
+    builder.app().name("app".to_owned());
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "app: server: missing field `host`");
+
+    // Converting a value back into a builder keeps the nested values.
+    let mut builder = AppConfigBuilder::from(config);
+    builder.server().port(9090);
+    let config = builder.build().unwrap();
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 9090);
+
+    let mut server = Server::builder();
+    server.host("localhost".to_owned()).port(80);
+    let worker = Worker::builder()
+        .cache(cache::Cache::builder().size(64))
+        .server(server)
+        .build()
+        .unwrap();
+    assert_eq!(worker.cache.size, 64);
+    assert_eq!(worker.server.port, 80);
+    let error = Worker::builder().build().unwrap_err();
+    assert_eq!(error.to_string(), "cache: missing field `size`");
+
+    let job = Job::builder();
+    let job = job.retry(Retry::builder().attempts(3)).build().unwrap();
+    assert_eq!(job.retry.attempts, 3);
+
+    let mut quota = Quota::builder();
+    quota.limits().max(10);
+    quota.label().tag("free");
+    let quota = quota.build().unwrap();
+    assert_eq!(quota.limits.max, 10);
+    assert_eq!(quota.label.tag, "free");
+
+    let mut http = HttpServer::builder();
+    http.server().host("localhost".to_owned()).port(80);
+    assert_eq!(http.build().unwrap().server.port, 80);
+    assert!(HTTPServer::builder().build().is_err());
+
+    let mut wrapper = Wrapper::builder();
+    wrapper.inner().tag(7u8);
+    assert_eq!(wrapper.build().unwrap().inner.tag, 7);
+    let error = Wrapper::<u8>::builder().build().unwrap_err();
+    assert_eq!(error.to_string(), "inner: missing field `tag`");
+}
@@ -0,0 +1,90 @@
+// #[builder(try_setter)] generates a fallible `try_` setter next to the plain
+// one, accepting anything convertible with TryInto and returning the
+// conversion error at the call site.
+//
+//     fn try_port<VALUE: TryInto<u16>>(&mut self, port: VALUE)
+//         -> Result<&mut Self, VALUE::Error>
+//
+// Useful when the builder is fed from parsed input, e.g. an i64 from JSON or a
+// &str from the command line. On the struct it applies to every field, and the
+// setter follows the builder pattern and typestate like the plain setters.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+use std::num::TryFromIntError;
+
+#[derive(Builder)]
+pub struct Listen {
+    host: String,
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    backlog: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Name(String);
+
+impl TryFrom<&str> for Name {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            Err("empty name".to_owned())
+        } else {
+            Ok(Name(value.to_owned()))
+        }
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, try_setter)]
+pub struct Account {
+    name: Name,
+    age: u8,
+    nickname: Option<Name>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable", try_setter)]
+pub struct Limits {
+    max: u8,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let json_port: i64 = 8080;
+    let listen = Listen::builder()
+        .host("localhost".to_owned())
+        .try_port(json_port)?
+        .try_backlog(128u64)?
+        .build()
+        .unwrap();
+    assert_eq!(listen.port, 8080);
+    assert_eq!(listen.backlog, Some(128));
+
+    let mut builder = Listen::builder();
+    assert!(builder.try_port(-1i64).is_err());
+    assert!(builder.try_port(70_000u32).is_err());
+
+    let account = Account::builder()
+        .try_name("ferris")
+        .unwrap()
+        .try_age(7u64)?
+        .try_nickname("crab")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(account.name, Name("ferris".to_owned()));
+    assert_eq!(account.age, 7);
+    assert_eq!(account.nickname, Some(Name("crab".to_owned())));
+    assert_eq!(
+        Account::builder().try_name("").err(),
+        Some("empty name".to_owned())
+    );
+
+    let limits = Limits::builder();
+    assert!(limits.try_max(300).is_err());
+    assert_eq!(limits.try_max(30)?.build().unwrap().max, 30);
+
+    Ok(())
+}
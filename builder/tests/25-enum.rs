@@ -0,0 +1,79 @@
+// Deriving Builder on an enum generates one builder per variant, created by a
+// `{variant}_builder()` constructor on the enum and building that variant.
+//
+//     Shape::circle_builder() -> ShapeCircleBuilder
+//     ShapeCircleBuilder::build() -> Result<Shape, ShapeCircleBuilderError>
+//
+// Tuple variants get positional setters like tuple structs, unit variants a
+// builder without setters. Options on the enum apply to every variant's
+// builder, #[builder(...)] on a variant itself is an error. The builders carry
+// the enum's generic parameters even when a variant doesn't use all of them.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    Rect {
+        width: f64,
+        height: f64,
+        #[builder(default = "1.0")]
+        scale: f64,
+    },
+    Point(f64, #[builder(name = "y")] f64),
+    Empty,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub enum Event<'a, T> {
+    Message { from: &'a str, payload: T },
+    Tick { count: u64 },
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(2.0).build().unwrap();
+    assert_eq!(
+        circle,
+        Shape::Circle {
+            radius: 2.0,
+            label: None,
+        }
+    );
+
+    let rect = Shape::rect_builder()
+        .width(3.0)
+        .height(4.0)
+        .build()
+        .unwrap();
+    assert_eq!(
+        rect,
+        Shape::Rect {
+            width: 3.0,
+            height: 4.0,
+            scale: 1.0,
+        }
+    );
+
+    let error: ShapeRectBuilderError = Shape::rect_builder().width(3.0).build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `height`");
+
+    let point = Shape::point_builder()._0(1.0).y(2.0).build().unwrap();
+    assert_eq!(point, Shape::Point(1.0, 2.0));
+    let error = Shape::point_builder().y(2.0).build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `_0`");
+    assert_eq!(Shape::empty_builder().build().unwrap(), Shape::Empty);
+
+    let message: Event<'_, Vec<u8>> = Event::message_builder()
+        .from("ferris")
+        .payload(vec![1, 2])
+        .build()
+        .unwrap();
+    assert!(matches!(message, Event::Message { from: "ferris", .. }));
+
+    let tick: Event<'static, ()> = Event::tick_builder().count(3).build().unwrap();
+    assert!(matches!(tick, Event::Tick { count: 3 }));
+}
@@ -0,0 +1,12 @@
+// Unions can't get a builder, which is reported instead of generating an empty
+// one.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+// Tuple structs get positional setters named after the field index, `_0`,
+// `_1`, ..., or after #[builder(name = "...")] on the field. Unit structs get a
+// builder without setters whose build() always succeeds.
+//
+//     Rgb::builder().red(255)._1(128).blue(0).build()
+//
+// All other field options, such as defaults and setter(into), work as for
+// named fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Rgb(#[builder(name = "red")] u8, u8, #[builder(name = "blue")] u8);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct UserId(#[builder(setter(into))] String);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Port(#[builder(default = "8080")] u16);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Marker;
+
+fn main() {
+    let color = Rgb::builder().red(255)._1(128).blue(0).build().unwrap();
+    assert_eq!(color, Rgb(255, 128, 0));
+
+    let error = Rgb::builder().red(255).blue(0).build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `_1`");
+
+    let id = UserId::builder()._0("ferris").build().unwrap();
+    assert_eq!(id, UserId("ferris".to_owned()));
+
+    assert_eq!(Port::builder().build().unwrap(), Port(8080));
+
+    assert_eq!(Marker::builder().build().unwrap(), Marker);
+}
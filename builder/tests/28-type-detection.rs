@@ -0,0 +1,67 @@
+// Option and Vec fields are recognized written as `Option<T>` / `Vec<T>` or by
+// their full std, core and alloc paths, and only the outermost Option is
+// unwrapped: an `Option<Vec<String>>` setter takes a `Vec<String>`.
+//
+// Types that merely share the name, like `my::Vec<T>`, are ordinary required
+// fields. For type aliases the macro can't see through, #[builder(option)]
+// marks a field as optional and #[builder(not_option)] marks an Option-looking
+// field as required, its setter then taking the whole type.
+
+use derive_builder::Builder;
+
+mod my {
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Vec<T>(pub T);
+
+    pub type MaybePort = std::option::Option<u16>;
+}
+
+extern crate alloc;
+
+#[derive(Builder)]
+pub struct Config {
+    tags: Option<Vec<String>>,
+    nested: Option<Option<u8>>,
+    host: std::option::Option<String>,
+    user: ::core::option::Option<String>,
+    args: alloc::vec::Vec<String>,
+    wrapper: my::Vec<u8>,
+    #[builder(option)]
+    port: my::MaybePort,
+    #[builder(not_option)]
+    timeout: Option<u32>,
+}
+
+fn main() {
+    let config = Config::builder()
+        .tags(vec!["a".to_owned()])
+        .nested(Some(1))
+        .host("localhost".to_owned())
+        .wrapper(my::Vec(7))
+        .port(Some(8080))
+        .timeout(None)
+        .build()
+        .unwrap();
+    assert_eq!(config.tags, Some(vec!["a".to_owned()]));
+    assert_eq!(config.nested, Some(Some(1)));
+    assert_eq!(config.host.as_deref(), Some("localhost"));
+    assert_eq!(config.user, None);
+    assert!(config.args.is_empty());
+    assert_eq!(config.wrapper, my::Vec(7));
+    assert_eq!(config.port, Some(8080));
+    assert_eq!(config.timeout, None);
+
+    // Neither the my::Vec nor the not_option field is optional.
+    let error = Config::builder().port(None).build().err().unwrap();
+    assert_eq!(error.to_string(), "missing field `wrapper`");
+    let error = Config::builder().wrapper(my::Vec(7)).build().err().unwrap();
+    assert_eq!(error.to_string(), "missing field `timeout`");
+
+    // An unset option field is None.
+    let config = Config::builder()
+        .wrapper(my::Vec(7))
+        .timeout(Some(3))
+        .build()
+        .unwrap();
+    assert_eq!(config.port, None);
+}
@@ -0,0 +1,45 @@
+// Every malformed #[builder(...)] attribute is reported in one compilation,
+// across the struct and all of its fields, each error pointing at the
+// offending key. Unknown keys close to a known one get a suggestion, and keys
+// such as `each` that need a value say so instead of panicking in the macro.
+// Values that don't parse are pointed at themselves. Enum variants take no
+// options of their own.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned", typestate)]
+pub struct Command {
+    #[builder(eahc = "arg")]
+    args: Vec<String>,
+    #[builder(each)]
+    env: Vec<String>,
+    #[builder(setter(int), default)]
+    current_dir: Option<String>,
+    #[builder(colour = "red")]
+    color: String,
+}
+
+#[derive(Builder)]
+#[builder(validate = "check(1)", vis = "public")]
+#[builder(name = "Server Builder", build_fn(name = "finish()"))]
+pub struct Server {
+    #[builder(default = "1 +")]
+    port: u16,
+    #[builder(name = "not an ident")]
+    host: String,
+    #[builder(compute = "host +")]
+    url: String,
+    #[builder(each = 3)]
+    ports: Vec<u16>,
+    #[builder(each(name = "not an ident"))]
+    hosts: Vec<String>,
+}
+
+#[derive(Builder)]
+pub enum Shape {
+    #[builder(default)]
+    Circle { radius: f64 },
+}
+
+fn main() {}
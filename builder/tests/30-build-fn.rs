@@ -0,0 +1,76 @@
+// The generated names can be changed on the struct:
+//
+//   - #[builder(name = "ConnOptions")] names the builder, and its error type
+//     becomes ConnOptionsError, e.g. to avoid a collision with an existing
+//     `ConnBuilder` type,
+//   - #[builder(build_fn(name = "finish"))] renames build(),
+//   - #[builder(build_fn(error = "MyError"))] makes it return the crate's own
+//     error type, converted from the builder's error with `From`,
+//   - #[builder(build_fn(skip))] generates no build method, so one can be
+//     written by hand.
+
+use derive_builder::Builder;
+
+// Hand-written type the derived builder must not collide with.
+pub struct ConnBuilder;
+
+#[derive(Builder, Debug)]
+#[builder(name = "ConnOptions", build_fn(name = "finish", error = "AppError"))]
+pub struct Conn {
+    host: String,
+    port: Option<u16>,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    Config(String),
+}
+
+impl From<ConnOptionsError> for AppError {
+    fn from(error: ConnOptionsError) -> Self {
+        AppError::Config(error.to_string())
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(build_fn(skip))]
+pub struct Range {
+    min: u32,
+    max: u32,
+}
+
+impl RangeBuilder {
+    pub fn build(&self) -> Result<Range, String> {
+        let min = self.min.unwrap_or(0);
+        let max = self.max.ok_or("missing max")?;
+        if min > max {
+            return Err(format!("{} > {}", min, max));
+        }
+        Ok(Range { min, max })
+    }
+}
+
+fn connect() -> Result<Conn, AppError> {
+    let conn = Conn::builder().host("localhost".to_owned()).finish()?;
+    Ok(conn)
+}
+
+fn main() {
+    let _ = ConnBuilder;
+    let options: ConnOptions = Conn::builder();
+    let AppError::Config(message) = options.finish().unwrap_err();
+    assert_eq!(message, "missing field `host`");
+
+    let conn = connect().unwrap();
+    assert_eq!(conn.host, "localhost");
+    assert_eq!(conn.port, None);
+
+    assert_eq!(
+        Range::builder().max(3).build(),
+        Ok(Range { min: 0, max: 3 })
+    );
+    assert_eq!(
+        Range::builder().min(4).max(3).build(),
+        Err("4 > 3".to_owned())
+    );
+}
@@ -0,0 +1,122 @@
+// #[builder(skip)] leaves a field out of the builder: no setter, and build()
+// fills it with Default::default(), or with `default = "expr"` when given.
+//
+// #[builder(compute = "expr")] also leaves the field out, and derives it from
+// the other fields inside build(). The expression sees the fields built so far
+// by reference under their own names, computed ones in declaration order, and
+// runs before any `validate` hook. The field's type needs no Default.
+//
+// A generic parameter only used by skipped fields is still accepted, the
+// builder holds a PhantomData of it.
+//
+//     #[builder(compute = "urls::url(host, *port)")]
+//     url: String,
+//
+// A function path is called with references to the fields of the builder,
+// in declaration order, leaving out the skipped and computed ones.
+//
+//     #[builder(compute = "urls::link")]
+//     link: String,
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(compute = "urls::url(host, *port)")]
+    url: String,
+    #[builder(compute = "format!(\"{}/health\", url)")]
+    health: String,
+    #[builder(compute = "urls::link")]
+    link: String,
+    #[builder(skip)]
+    cache: HashMap<String, String>,
+    #[builder(skip, default = "3")]
+    retries: u8,
+}
+
+mod urls {
+    pub fn url(host: &str, port: u16) -> String {
+        format!("http://{}:{}", host, port)
+    }
+
+    pub fn link(host: &str, port: &u16) -> String {
+        format!("<a href=\"http://{}:{}\">{}</a>", host, port, host)
+    }
+}
+
+// No Default for the computed type.
+#[derive(Debug, PartialEq)]
+pub struct Area(f64);
+
+#[derive(Builder, Debug)]
+pub enum Shape {
+    Rect {
+        width: f64,
+        height: f64,
+        #[builder(compute = "Area(width * height)")]
+        area: Area,
+    },
+}
+
+#[derive(Builder, Debug)]
+pub struct Pair<T: Clone>(T, #[builder(compute = "(_0.clone(), 2)")] (T, u8));
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    #[builder(skip)]
+    attempts: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct Typed<T> {
+    id: u32,
+    #[builder(skip)]
+    kind: PhantomData<T>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Handle<'a, T> {
+    id: u32,
+    #[builder(skip)]
+    target: PhantomData<&'a T>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+    assert_eq!(server.url, "http://localhost:8080");
+    assert_eq!(server.health, "http://localhost:8080/health");
+    assert_eq!(
+        server.link,
+        "<a href=\"http://localhost:8080\">localhost</a>"
+    );
+    assert!(server.cache.is_empty());
+    assert_eq!(server.retries, 3);
+
+    let rect = Shape::rect_builder().width(2.0).height(3.0).build().unwrap();
+    assert!(matches!(rect, Shape::Rect { area: Area(6.0), .. }));
+
+    let pair = Pair::builder()._0("a").build().unwrap();
+    assert_eq!(pair.1, ("a", 2));
+
+    let job = Job::builder().name("backup".to_owned()).build().unwrap();
+    assert_eq!(job.name, "backup");
+    assert_eq!(job.attempts, 0);
+
+    let typed = Typed::<String>::builder().id(1).build().unwrap();
+    assert_eq!(typed.id, 1);
+    assert_eq!(typed.kind, PhantomData);
+    let handle = Handle::<'_, String>::builder().id(2).build().unwrap();
+    assert_eq!(handle.id, 2);
+    assert_eq!(handle.target, PhantomData);
+}
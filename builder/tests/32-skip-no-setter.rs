@@ -0,0 +1,14 @@
+// Skipped and computed fields have no setter.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(skip)]
+    connections: u32,
+}
+
+fn main() {
+    let _ = Server::builder().host("localhost".to_owned()).connections(3);
+}
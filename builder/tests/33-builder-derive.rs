@@ -0,0 +1,56 @@
+// The builder struct can carry extra derives and attributes:
+//
+//   - #[builder(derive(Clone, Debug, PartialEq))] derives traits on the
+//     builder, so partially built builders can be kept in fixtures, compared
+//     and logged,
+//   - #[builder(struct_attr(...))] puts any attribute on the builder struct,
+//   - #[builder(field_attr(...))] on a field puts an attribute on the
+//     builder's field for it.
+//
+// The immutable pattern's own Clone derive is not repeated when Clone is
+// listed.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(
+    derive(Clone, Debug, PartialEq),
+    struct_attr(must_use = "a builder does nothing until built"),
+    struct_attr(derive(Default))
+)]
+pub struct Request {
+    #[builder(field_attr(doc = "Target of the request."))]
+    url: String,
+    #[builder(field_attr(allow(dead_code)))]
+    retries: Option<u8>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable", derive(std::clone::Clone, Debug))]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut fixture = Request::builder();
+    fixture.url("https://example.com".to_owned());
+
+    let mut with_retries = fixture.clone();
+    with_retries.retries(3);
+    assert_ne!(fixture, with_retries);
+    assert_eq!(fixture, fixture.clone());
+    assert_eq!(
+        format!("{:?}", fixture),
+        r#"RequestBuilder { url: Some("https://example.com"), retries: None }"#
+    );
+    assert_eq!(RequestBuilder::default(), Request::builder());
+
+    let request = with_retries.build().unwrap();
+    assert_eq!(request.retries, Some(3));
+
+    let origin = Point::builder().x(0).y(0);
+    let point = origin.x(1).build().unwrap();
+    assert_eq!((point.x, point.y), (1, 0));
+    assert_eq!(format!("{:?}", origin), "PointBuilder { x: Some(0), y: Some(0) }");
+}
@@ -0,0 +1,117 @@
+// Partial builders can be layered, e.g. defaults, a config file, environment
+// and command line flags each filling in some fields:
+//
+//   - merge(other) lets every field set in `other` override this builder's,
+//   - or(fallback) only fills the fields still unset from `fallback`.
+//
+// Std collections with an `each` setter are appended to rather than
+// replaced, the lower layer's items first, while a Vec set as a whole is
+// overridden like any other field. sub_builder fields are merged field by
+// field. Both follow the builder pattern like the setters do; they aren't
+// generated for typestate builders.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+pub struct Config {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    #[builder(each = "include")]
+    includes: Vec<String>,
+    #[builder(each = "label")]
+    labels: HashMap<String, String>,
+    args: Vec<String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Flags {
+    verbose: bool,
+    quiet: bool,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults
+        .host("localhost".to_owned())
+        .port(80)
+        .include("base.toml".to_owned())
+        .label("env".to_owned(), "dev".to_owned())
+        .args(vec!["default".to_owned()]);
+
+    let mut file = Config::builder();
+    file.port(8080)
+        .include("app.toml".to_owned())
+        .label("env".to_owned(), "prod".to_owned());
+
+    let mut cli = Config::builder();
+    cli.user("ferris".to_owned()).args(vec!["cli".to_owned()]);
+
+    let config = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.user.as_deref(), Some("ferris"));
+    assert_eq!(config.includes, ["base.toml", "app.toml"]);
+    assert_eq!(config.labels["env"], "prod");
+    assert_eq!(config.args, ["cli"]);
+
+    // The same layers from the top: cli, then file, then defaults.
+    let mut cli = Config::builder();
+    cli.port(9090).include("cli.toml".to_owned());
+    let mut file = Config::builder();
+    file.host("example.com".to_owned()).port(8080);
+    let mut defaults = Config::builder();
+    defaults.host("localhost".to_owned()).include("base.toml".to_owned());
+
+    let config = cli.or(file).or(defaults).build().unwrap();
+    assert_eq!(config.host, "example.com");
+    assert_eq!(config.port, 9090);
+    assert_eq!(config.user, None);
+    assert_eq!(config.includes, ["base.toml", "cli.toml"]);
+
+    // Nested builders set in both layers keep the fields of each.
+    let mut defaults = Service::builder();
+    defaults.name("api".to_owned()).server().host("localhost".to_owned()).port(80);
+    let mut file = Service::builder();
+    file.server().port(8080).alias("api.local".to_owned());
+    let mut cli = Service::builder();
+    cli.server().alias("api.test".to_owned());
+    let service = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(service.server.host, "localhost");
+    assert_eq!(service.server.port, 8080);
+    assert_eq!(service.server.aliases, ["api.local", "api.test"]);
+
+    let mut file = Service::builder();
+    file.server().port(8080);
+    let mut defaults = Service::builder();
+    defaults.name("api".to_owned()).server().host("localhost".to_owned()).port(80);
+    let service = file.or(defaults).build().unwrap();
+    assert_eq!(service.server.host, "localhost");
+    assert_eq!(service.server.port, 8080);
+
+    let flags = Flags::builder()
+        .verbose(false)
+        .quiet(false)
+        .merge(Flags::builder().verbose(true))
+        .build()
+        .unwrap();
+    assert!(flags.verbose);
+    assert!(!flags.quiet);
+}
@@ -0,0 +1,64 @@
+// The generated code only relies on core, so builders work in #![no_std]
+// crates. The error type implements core::fmt::Display and
+// core::error::Error.
+//
+// Sub-builder and validation errors need alloc's Box and String. Opt into
+// those with #[builder(no_std)], with `extern crate alloc;` in the crate root.
+//
+// This test links std under another name, so any `std::` path left in the
+// generated code fails to resolve.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as host;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Packet {
+    id: u16,
+    payload: Vec<u8>,
+    #[builder(each = "flag")]
+    flags: Vec<u8>,
+    checksum: Option<u32>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std, validate = "check")]
+pub struct Frame {
+    #[builder(sub_builder)]
+    packet: Packet,
+    name: String,
+}
+
+fn check(frame: &Frame) -> Result<(), &'static str> {
+    if frame.name.is_empty() {
+        Err("empty name")
+    } else {
+        Ok(())
+    }
+}
+
+fn assert_error<E: core::error::Error + core::fmt::Display>(_: &E) {}
+
+fn main() {
+    let packet = Packet::builder().id(7).flag(1).flag(2).build().unwrap();
+    assert_eq!(packet.id, 7);
+    assert!(packet.payload.is_empty());
+    assert_eq!(packet.flags, [1, 2]);
+
+    let error = Packet::builder().build().unwrap_err();
+    assert_error(&error);
+    assert!(Packet::builder().missing_fields().eq(["id"]));
+
+    let mut frame = Frame::builder();
+    frame.packet().id(1);
+    let error = frame.name(String::new()).build().unwrap_err();
+    assert!(matches!(error, FrameBuilderError::ValidationError(_)));
+
+    let frame = frame.name(String::from("f")).build().unwrap();
+    assert_eq!(frame.packet.id, 1);
+}
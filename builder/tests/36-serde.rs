@@ -0,0 +1,63 @@
+// With the `serde` cargo feature, #[builder(serde)] makes the builder
+// implement serde::Deserialize with every field optional, so a config file
+// can be loaded into a partial builder, supplemented in code, and checked by
+// build(), which reports the fields still missing.
+//
+// Typestate builders and builders of structs with lifetime parameters can't
+// implement Deserialize, and neither can fields of types that don't.
+//
+// Only run with `cargo test --features serde`.
+
+use derive_builder::Builder;
+
+// The derive names the serde crate by its absolute path, so a local `serde`
+// module doesn't get in the way.
+#[allow(dead_code)]
+mod serde {}
+
+#[derive(Builder, Debug)]
+#[builder(serde)]
+pub struct Config {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    #[builder(each = "include")]
+    includes: Vec<String>,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+#[builder(serde)]
+pub struct Limits {
+    #[builder(default = "16")]
+    connections: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(serde, struct_attr(serde(deny_unknown_fields)))]
+pub struct Strict {
+    name: String,
+}
+
+fn main() {
+    let mut builder: ConfigBuilder = serde_json::from_str(
+        r#"{ "host": "localhost", "includes": ["base.toml"], "limits": {} }"#,
+    )
+    .unwrap();
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `port`");
+
+    let config = builder
+        .port(8080)
+        .include("app.toml".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.user, None);
+    assert_eq!(config.includes, ["base.toml", "app.toml"]);
+    assert_eq!(config.limits.connections, 16);
+
+    assert!(serde_json::from_str::<StrictBuilder>(r#"{ "nmae": "typo" }"#).is_err());
+}
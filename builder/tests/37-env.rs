@@ -0,0 +1,90 @@
+// Fields marked #[builder(env = "VAR")] can be read from environment
+// variables, parsed with FromStr:
+//
+//   - fill_from_env() sets the fields whose variable is present, following
+//     the builder pattern like the setters do,
+//   - XBuilder::from_env() starts from an empty builder.
+//
+// Unset variables leave the field untouched, so build() still reports it as
+// missing. A value that fails to parse is reported as
+// `XBuilderError::EnvError` naming the variable.
+//
+// Not supported with typestate or no_std builders.
+
+use derive_builder::Builder;
+use std::net::IpAddr;
+
+#[derive(Builder, Debug)]
+pub struct Config {
+    #[builder(env = "DERIVE_BUILDER_TEST_HOST")]
+    host: IpAddr,
+    #[builder(env = "DERIVE_BUILDER_TEST_PORT")]
+    port: u16,
+    #[builder(env = "DERIVE_BUILDER_TEST_USER")]
+    user: Option<String>,
+    #[builder(env = "DERIVE_BUILDER_TEST_WORKERS", default = "4")]
+    workers: usize,
+    name: String,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Service {
+    #[builder(env = "DERIVE_BUILDER_TEST_SERVICE")]
+    service: String,
+    #[builder(env = "DERIVE_BUILDER_TEST_DEBUG")]
+    debug: bool,
+}
+
+fn main() {
+    std::env::set_var("DERIVE_BUILDER_TEST_HOST", "127.0.0.1");
+    std::env::set_var("DERIVE_BUILDER_TEST_PORT", "8080");
+    std::env::remove_var("DERIVE_BUILDER_TEST_USER");
+    std::env::remove_var("DERIVE_BUILDER_TEST_WORKERS");
+
+    let config = ConfigBuilder::from_env()
+        .unwrap()
+        .name("api".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(config.host, IpAddr::from([127, 0, 0, 1]));
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.user, None);
+    assert_eq!(config.workers, 4);
+    assert_eq!(config.name, "api");
+
+    // Variables override what was set before, unset ones keep it.
+    std::env::set_var("DERIVE_BUILDER_TEST_USER", "admin");
+    let mut builder = Config::builder();
+    builder.port(9090).workers(2).name("api".to_owned());
+    builder.fill_from_env().unwrap();
+    let config = builder.build().unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.user.as_deref(), Some("admin"));
+    assert_eq!(config.workers, 2);
+
+    // Parse failures name the variable.
+    std::env::set_var("DERIVE_BUILDER_TEST_PORT", "http");
+    let error = ConfigBuilder::from_env().err().unwrap();
+    assert!(matches!(error, ConfigBuilderError::EnvError("DERIVE_BUILDER_TEST_PORT", _)));
+    assert_eq!(
+        error.to_string(),
+        "environment variable `DERIVE_BUILDER_TEST_PORT`: invalid digit found in string"
+    );
+
+    std::env::remove_var("DERIVE_BUILDER_TEST_PORT");
+    let mut builder = ConfigBuilder::from_env().unwrap();
+    let error = builder.name("api".to_owned()).build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `port`");
+
+    std::env::set_var("DERIVE_BUILDER_TEST_SERVICE", "billing");
+    std::env::set_var("DERIVE_BUILDER_TEST_DEBUG", "true");
+    let service = Service::builder()
+        .debug(false)
+        .fill_from_env()
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(service.service, "billing");
+    assert!(service.debug);
+}
@@ -0,0 +1,20 @@
+// #[builder(env = "...")] parses a single value with FromStr, so it is
+// rejected on collections and nested builders, and on typestate builders
+// whose type tracks the set fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(env = "INCLUDES")]
+    includes: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    #[builder(env = "EXECUTABLE")]
+    executable: String,
+}
+
+fn main() {}
@@ -0,0 +1,132 @@
+// #[builder(from_args)] generates XBuilder::from_args(args) for small tools,
+// taking the arguments without the program name:
+//
+//   - `--field-name value` or `--field-name=value`, the flag being the
+//     kebab-cased field name, the value parsed with FromStr,
+//   - bool fields are switches, `--verbose` alone sets them, `--verbose=false`
+//     clears them, and build() takes them as false when left off,
+//   - collections take their flag repeatedly, maps as `--labels KEY=VALUE`,
+//   - `--help` fails with `XBuilderError::Help`, whose text lists the flags
+//     with the field doc comments.
+//
+// Unknown flags, missing values and parse failures are reported as
+// `XBuilderError::ArgsError`. Fields not set by flags stay unset, switches
+// included, so build() still applies defaults and reports missing fields, and
+// merging the parsed builder over another layer only overrides what was given.
+
+use derive_builder::Builder;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Copies files to a remote host.
+#[derive(Builder, Debug)]
+#[builder(from_args)]
+pub struct Upload {
+    /// Host to connect to.
+    remote_host: String,
+    /// Port of the host.
+    #[builder(default = "22")]
+    port: u16,
+    /// Print every file copied.
+    verbose: bool,
+    /// Compress files before sending them.
+    #[builder(default = "true")]
+    compress: bool,
+    /// File to copy.
+    #[builder(each = "file")]
+    files: Vec<PathBuf>,
+    /// Metadata attached to the upload.
+    #[builder(each = "label")]
+    labels: BTreeMap<String, String>,
+    user: Option<String>,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn main() {
+    let upload = UploadBuilder::from_args(args(&[
+        "--remote-host",
+        "example.com",
+        "--verbose",
+        "--files=a.txt",
+        "--files",
+        "b.txt",
+        "--labels",
+        "team=infra",
+    ]))
+    .unwrap()
+    .build()
+    .unwrap();
+    assert_eq!(upload.remote_host, "example.com");
+    assert_eq!(upload.port, 22);
+    assert!(upload.verbose);
+    assert!(upload.compress);
+    assert_eq!(upload.files, [PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+    assert_eq!(upload.labels["team"], "infra");
+    assert_eq!(upload.user, None);
+
+    // Flags fill a builder like setters do.
+    let mut builder = UploadBuilder::from_args(args(&["--port", "2222"])).unwrap();
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `remote_host`");
+    let upload = builder.remote_host("localhost".to_owned()).build().unwrap();
+    assert_eq!(upload.port, 2222);
+    assert!(!upload.verbose);
+    assert_eq!(builder.get_verbose(), None);
+    assert!(builder.unset_remote_host().missing_fields().eq(["remote_host"]));
+    let upload = UploadBuilder::from_args(args(&["--remote-host=localhost", "--compress=false"]))
+        .unwrap()
+        .build()
+        .unwrap();
+    assert!(!upload.compress);
+
+    // An empty command line overrides nothing of the layer below it.
+    let mut builder = Upload::builder();
+    builder
+        .remote_host("example.com".to_owned())
+        .verbose(true)
+        .compress(false);
+    builder.merge(UploadBuilder::from_args(args(&[])).unwrap());
+    let upload = builder.build().unwrap();
+    assert!(upload.verbose);
+    assert!(!upload.compress);
+    let mut builder = Upload::builder();
+    builder.verbose(true);
+    builder.merge(UploadBuilder::from_args(args(&["--verbose=false"])).unwrap());
+    assert_eq!(builder.get_verbose(), Some(&false));
+
+    let error = UploadBuilder::from_args(args(&["--port", "ssh"])).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "invalid value for `--port`: invalid digit found in string"
+    );
+    let error = UploadBuilder::from_args(args(&["--user"])).err().unwrap();
+    assert_eq!(error.to_string(), "missing value for `--user`");
+    let error = UploadBuilder::from_args(args(&["--labels", "infra"])).err().unwrap();
+    assert_eq!(error.to_string(), "expected `KEY=VALUE` for `--labels`");
+    let error = UploadBuilder::from_args(args(&["upload.txt"])).err().unwrap();
+    assert_eq!(error.to_string(), "unexpected argument `upload.txt`");
+
+    let error = UploadBuilder::from_args(args(&["--help"])).err().unwrap();
+    let help = match error {
+        UploadBuilderError::Help(help) => help,
+        error => panic!("expected the help, got {}", error),
+    };
+    assert_eq!(
+        help,
+        "\
+Copies files to a remote host.
+
+Options:
+  --remote-host <REMOTE-HOST>  Host to connect to. (required)
+  --port <PORT>                Port of the host.
+  --verbose                    Print every file copied.
+  --compress                   Compress files before sending them.
+  --files <FILES>              File to copy. (repeatable)
+  --labels <KEY=VALUE>         Metadata attached to the upload. (repeatable)
+  --user <USER>
+  --help                       Print this help."
+    );
+}
@@ -0,0 +1,127 @@
+// Builders can be inspected before build(), e.g. by a UI asking for the values
+// still outstanding:
+//
+//   - missing_fields() iterates over the names of the unset fields build()
+//     would report as missing, as `&'static str` in field order, those of
+//     nested builders as `server.port`, without allocating,
+//   - is_set_field() and get_field(), an `Option<&T>` of the value set,
+//     `Option<&U>` for an `Option<U>` field and the nested builder for a
+//     sub_builder field,
+//   - unset_field() and clear() reset one or all fields, following the
+//     builder pattern like the setters do.
+//
+// Skipped and computed fields have no accessors. Typestate builders only get
+// missing_fields(), is_set_ and get_, since unsetting would change their type.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Config {
+    host: String,
+    port: u16,
+    user: Option<String>,
+    #[builder(default = "4")]
+    workers: usize,
+    #[builder(each = "include")]
+    includes: Vec<String>,
+    #[builder(sub_builder)]
+    limits: Limits,
+    #[builder(skip)]
+    connections: usize,
+}
+
+#[derive(Builder, Debug)]
+pub struct Limits {
+    #[builder(default = "16")]
+    max: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct App {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    #[builder(default = "80")]
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder, Debug)]
+pub struct Tls {
+    cert: String,
+    key: String,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Owned {
+    name: String,
+    id: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    assert_eq!(builder.missing_fields().collect::<Vec<_>>(), ["host", "port"]);
+
+    builder.host("localhost".to_owned()).user("admin".to_owned());
+    assert_eq!(builder.missing_fields().collect::<Vec<_>>(), ["port"]);
+    assert!(builder.is_set_host());
+    assert!(!builder.is_set_port());
+    assert!(!builder.is_set_workers());
+    assert_eq!(builder.get_host().map(String::as_str), Some("localhost"));
+    assert_eq!(builder.get_user().map(String::as_str), Some("admin"));
+    assert_eq!(builder.get_port(), None);
+
+    builder.include("a.toml".to_owned());
+    builder.limits().max(8);
+    assert_eq!(builder.get_includes().map(Vec::len), Some(1));
+    assert_eq!(builder.get_limits().and_then(LimitsBuilder::get_max), Some(&8));
+
+    builder.port(8080).unset_host();
+    assert_eq!(builder.missing_fields().collect::<Vec<_>>(), ["host"]);
+    assert_eq!(builder.build().unwrap_err().to_string(), "missing field `host`");
+    let config = builder.host("example.com".to_owned()).build().unwrap();
+    assert_eq!(config.connections, 0);
+    assert_eq!(config.limits.max, 8);
+
+    builder.clear();
+    assert!(!builder.is_set_user());
+    assert!(!builder.is_set_limits());
+    assert_eq!(builder.missing_fields().count(), 2);
+
+    // Nested builders report their own missing fields, built empty when unset.
+    let mut app = App::builder();
+    assert_eq!(app.missing_fields().collect::<Vec<_>>(),
+        ["name", "server.host", "server.tls.cert", "server.tls.key"]
+    );
+    app.name("api".to_owned()).server().tls().key("key.pem".to_owned());
+    assert_eq!(app.missing_fields().collect::<Vec<_>>(), ["server.host", "server.tls.cert"]);
+    app.server().host("localhost".to_owned()).tls().cert("cert.pem".to_owned());
+    assert_eq!(app.missing_fields().next(), None);
+    assert_eq!(app.build().unwrap().server.port, 80);
+
+    let builder = Owned::builder().name("a".to_owned()).id(1).unset_name();
+    assert_eq!(builder.missing_fields().collect::<Vec<_>>(), ["name"]);
+    let builder = builder.clear();
+    assert_eq!(builder.missing_fields().collect::<Vec<_>>(), ["name", "id"]);
+
+    let command = Command::builder();
+    assert_eq!(command.missing_fields().collect::<Vec<_>>(), ["executable"]);
+    let command = command.executable("cargo".to_owned());
+    assert_eq!(command.missing_fields().next(), None);
+    assert_eq!(command.get_executable().map(String::as_str), Some("cargo"));
+    assert!(!command.is_set_args());
+}
@@ -0,0 +1,39 @@
+// The generated code refers to core and alloc through absolute paths,
+// `::core::option::Option`, so a local module named `core` doesn't break it.
+//
+// Only the macro's own paths depend on #[builder(no_std)]: types and
+// expressions written by the user are emitted as written, here a std HashMap
+// in a builder whose errors use alloc's String.
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+mod core {}
+
+#[derive(Builder, Debug)]
+#[builder(no_std, validate = "check")]
+pub struct Routes {
+    #[builder(default = "std::collections::HashMap::new()")]
+    table: std::collections::HashMap<String, u16>,
+    name: Option<String>,
+}
+
+fn check(routes: &Routes) -> Result<(), &'static str> {
+    if routes.table.contains_key("") {
+        Err("empty route")
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    let routes = Routes::builder().build().unwrap();
+    assert!(routes.table.is_empty());
+    assert_eq!(routes.name, None);
+
+    let mut table = std::collections::HashMap::new();
+    table.insert(String::new(), 80);
+    let error = Routes::builder().table(table).build().unwrap_err();
+    assert_eq!(error.to_string(), "empty route");
+}
@@ -0,0 +1,36 @@
+// Nested builders that a parent's build() can't use are rejected when the
+// parent compiles, pointing at the field: typestate builders, whose type
+// changes as fields are set, builders without a generated build method, and
+// owned builders nested in a builder that builds by reference.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(skip))]
+pub struct Manual {
+    value: u8,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Owned {
+    value: u8,
+}
+
+#[derive(Builder)]
+pub struct Parent {
+    #[builder(sub_builder)]
+    command: Command,
+    #[builder(sub_builder)]
+    manual: Manual,
+    #[builder(sub_builder)]
+    owned: Owned,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+// Builder methods generated for fields must not clash with the builder's own
+// methods, merge(), or(), clear(), missing_fields() and build(), nor with the
+// methods of other fields. Each clash is reported on the field, naming the
+// option that renames its methods.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Cache {
+    clear: bool,
+    #[builder(each = "merge")]
+    layers: Vec<String>,
+    port: u16,
+    get_port: u16,
+    #[builder(name = "or_else")]
+    or: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(name = "finish"))]
+pub struct Job {
+    build: bool,
+    finish: bool,
+}
+
+fn main() {}
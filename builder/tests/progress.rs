@@ -10,4 +10,39 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-missing-field-error.rs");
+    t.pass("tests/11-generic-struct.rs");
+    t.pass("tests/12-typestate.rs");
+    t.compile_fail("tests/13-typestate-missing-field.rs");
+    t.pass("tests/14-default.rs");
+    t.pass("tests/15-setter-into.rs");
+    t.pass("tests/16-builder-pattern.rs");
+    t.compile_fail("tests/17-unrecognized-pattern.rs");
+    t.pass("tests/18-visibility.rs");
+    t.compile_fail("tests/19-private-setter.rs");
+    t.pass("tests/20-validate.rs");
+    t.pass("tests/21-each-collections.rs");
+    t.pass("tests/22-to-builder.rs");
+    t.pass("tests/23-sub-builder.rs");
+    t.pass("tests/24-try-setter.rs");
+    t.pass("tests/25-enum.rs");
+    t.compile_fail("tests/26-union.rs");
+    t.pass("tests/27-tuple-struct.rs");
+    t.pass("tests/28-type-detection.rs");
+    t.compile_fail("tests/29-attribute-errors.rs");
+    t.pass("tests/30-build-fn.rs");
+    t.pass("tests/31-skip-compute.rs");
+    t.compile_fail("tests/32-skip-no-setter.rs");
+    t.pass("tests/33-builder-derive.rs");
+    t.pass("tests/34-merge.rs");
+    t.pass("tests/35-no-std.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/36-serde.rs");
+    t.pass("tests/37-env.rs");
+    t.compile_fail("tests/38-env-unsupported.rs");
+    t.pass("tests/39-from-args.rs");
+    t.pass("tests/40-introspection.rs");
+    t.pass("tests/41-core-paths.rs");
+    t.compile_fail("tests/42-sub-builder-unsupported.rs");
+    t.compile_fail("tests/43-method-conflicts.rs");
 }